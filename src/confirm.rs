@@ -0,0 +1,95 @@
+use crate::error::PrepareError;
+use anyhow::Result;
+use std::io::{BufRead, IsTerminal, Write};
+
+/// How the destructive modes, like --prune, get confirmed before deleting
+/// or rewriting pages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Confirm {
+    /// Proceed without asking, with --yes
+    Yes,
+    /// Ask on the terminal
+    Prompt,
+    /// Nobody to ask: refuse to proceed
+    Refuse,
+}
+
+impl Confirm {
+    /// Ask only when running in a terminal, unless `yes`
+    pub fn new(yes: bool) -> Self {
+        if yes {
+            Self::Yes
+        } else if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+            Self::Prompt
+        } else {
+            Self::Refuse
+        }
+    }
+
+    /// Check that `action`, e.g. "delete 4 pages", may proceed
+    pub fn confirm(&self, action: &str) -> Result<()> {
+        let confirmed = match self {
+            Self::Yes => true,
+            Self::Prompt => ask(action, std::io::stdin().lock(), std::io::stdout())?,
+            Self::Refuse => false,
+        };
+        match confirmed {
+            true => Ok(()),
+            false => Err(PrepareError::NotConfirmed {
+                action: action.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// `count` pages, as said in the confirmation prompts
+pub fn pages(count: usize) -> String {
+    match count {
+        1 => "1 page".to_owned(),
+        count => format!("{count} pages"),
+    }
+}
+
+/// Ask on `output` whether to `action`, reading the answer from `input`.
+/// Only `y` or `yes` confirm
+fn ask<R: BufRead, W: Write>(action: &str, mut input: R, mut output: W) -> std::io::Result<bool> {
+    write!(output, "About to {action}, proceed? [y/N] ")?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ask() -> anyhow::Result<()> {
+        for (answer, confirmed) in [("y\n", true), ("YES\n", true), ("n\n", false), ("", false)] {
+            let mut output = Vec::new();
+            assert_eq!(
+                confirmed,
+                super::ask("delete 4 pages", answer.as_bytes(), &mut output)?
+            );
+            assert_eq!(
+                "About to delete 4 pages, proceed? [y/N] ",
+                String::from_utf8(output)?
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn confirm() {
+        assert!(Confirm::new(true).confirm("delete 4 pages").is_ok());
+        let error = Confirm::Refuse.confirm("delete 4 pages").unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(PrepareError::NotConfirmed { .. })
+        ));
+    }
+}
@@ -1,16 +1,88 @@
-use chrono::{Datelike, Days, IsoWeek, Months, NaiveDate, Weekday};
+use chrono::{DateTime, Datelike, Days, IsoWeek, Months, NaiveDate, SecondsFormat, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// Local date at the instant `now` in `timezone`, going through the zone
+/// rules so that DST transitions land on the right day
+pub fn today(now: DateTime<Utc>, timezone: Option<Tz>) -> NaiveDate {
+    match timezone {
+        Some(timezone) => now.with_timezone(&timezone).date_naive(),
+        None => now.date_naive(),
+    }
+}
+
+/// ISO 8601 timestamp of the instant `now` in `timezone`
+pub fn timestamp(now: DateTime<Utc>, timezone: Option<Tz>) -> String {
+    match timezone {
+        Some(timezone) => now
+            .with_timezone(&timezone)
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => now.to_rfc3339_opts(SecondsFormat::Secs, true),
+    }
+}
+
+/// From `today` to the last day of its month
+pub fn rest_of_month(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    (today, Month::from(today).last())
+}
+
+/// From `today` to the last day of its year
+pub fn rest_of_year(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    (today, Year::from(today.year()).last().last())
+}
+
+/// English name of `weekday`
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Days after `date` until `last`, the last day of the period holding it
+pub fn days_left(date: NaiveDate, last: NaiveDate) -> i64 {
+    (last - date).num_days()
+}
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, derive_more::From, derive_more::Display)]
 #[display("{:04}", _0)]
 pub struct Year(i32);
 
+/// How the month and week segments of the page names are written
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, clap::ValueEnum)]
+pub enum NameStyle {
+    /// `2024/September` and `2024/Week 36`
+    #[default]
+    Name,
+    /// `2024/09` and `2024/W36`
+    Numeric,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct Month {
     year: i32,
     month: u32,
+    style: NameStyle,
 }
 
 impl Month {
+    pub fn name_style(mut self, style: NameStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn style(&self) -> NameStyle {
+        self.style
+    }
+
+    pub fn number(&self) -> u32 {
+        self.month
+    }
+
     pub fn name(&self) -> &str {
         chrono::Month::try_from(self.month as u8).unwrap().name()
     }
@@ -20,11 +92,84 @@ impl Month {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Quarter {
+    year: i32,
+    quarter: u32,
+}
+
+impl Quarter {
+    pub fn quarter(&self) -> u32 {
+        self.quarter
+    }
+
+    pub fn year(&self) -> Year {
+        self.year.into()
+    }
+}
+
+/// Periods of `length` days one after the other, the first one starting on
+/// `anchor`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SprintCycle {
+    pub anchor: NaiveDate,
+    pub length: u32,
+}
+
+impl SprintCycle {
+    /// The sprint holding `date`, before the anchor too
+    pub fn sprint(&self, date: NaiveDate) -> Sprint {
+        Sprint {
+            cycle: *self,
+            index: (date - self.anchor)
+                .num_days()
+                .div_euclid(self.length.into()),
+        }
+    }
+
+    /// The sprint numbered `number`, see [`Sprint::number`]
+    pub fn numbered(&self, number: i64) -> Sprint {
+        Sprint {
+            cycle: *self,
+            index: number - 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprint {
+    cycle: SprintCycle,
+    /// Sprints since the one starting on the anchor, negative before it
+    index: i64,
+}
+
+impl Sprint {
+    /// Number of the sprint, 1 for the one starting on the anchor
+    pub fn number(&self) -> i64 {
+        self.index + 1
+    }
+}
+
+impl From<Month> for Quarter {
+    fn from(month: Month) -> Self {
+        Quarter {
+            year: month.year,
+            quarter: (month.month - 1) / 3 + 1,
+        }
+    }
+}
+impl From<NaiveDate> for Quarter {
+    fn from(date: NaiveDate) -> Self {
+        Self::from(Month::from(date))
+    }
+}
+
 impl From<NaiveDate> for Month {
     fn from(date: NaiveDate) -> Self {
         Month {
             year: date.year(),
             month: date.month(),
+            style: NameStyle::default(),
         }
     }
 }
@@ -33,6 +178,11 @@ impl From<IsoWeek> for Month {
         Self::from(week.first())
     }
 }
+impl From<Week> for Month {
+    fn from(week: Week) -> Self {
+        Self::from(week.first()).name_style(week.style)
+    }
+}
 impl std::ops::Add<Months> for Month {
     type Output = Self;
 
@@ -42,6 +192,7 @@ impl std::ops::Add<Months> for Month {
         Month {
             year: self.year + month.div_euclid(12) as i32,
             month: month.rem_euclid(12) + 1,
+            style: self.style,
         }
     }
 }
@@ -54,6 +205,139 @@ impl std::ops::Sub<Months> for Month {
         Month {
             year: self.year + month.div_euclid(12),
             month: month.rem_euclid(12) as u32 + 1,
+            style: self.style,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+/// Territories whose weeks start on Sunday, from the CLDR
+const SUNDAY_TERRITORIES: [&str; 52] = [
+    "AG", "AS", "BD", "BR", "BS", "BT", "BW", "BZ", "CA", "CN", "CO", "DM", "DO", "ET", "GT", "GU",
+    "HK", "HN", "ID", "IL", "IN", "JM", "JP", "KE", "KH", "KR", "LA", "MH", "MM", "MO", "MT", "MX",
+    "MZ", "NI", "NP", "PA", "PE", "PH", "PK", "PR", "PT", "PY", "SA", "SG", "SV", "TH", "TT", "TW",
+    "US", "VE", "ZA", "ZW",
+];
+
+impl WeekStart {
+    /// First day of the week of the locale, looked up by `var` in LC_ALL,
+    /// LC_TIME then LANG like the C library does. Monday when the locale has
+    /// no territory, e.g. `C` or `fr`
+    pub fn from_locale<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let locale = ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()));
+
+        // language[_territory][.codeset][@modifier]
+        let territory = locale.as_deref().and_then(|locale| {
+            let locale = locale.split(['.', '@']).next()?;
+            locale.split_once('_').map(|(_, territory)| territory)
+        });
+
+        match territory {
+            Some(territory) if SUNDAY_TERRITORIES.contains(&territory) => WeekStart::Sunday,
+            _ => WeekStart::Monday,
+        }
+    }
+}
+
+/// Year in the name of the week pages
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum WeekYear {
+    /// ISO week-year, e.g. `2025/Week 01` from Monday 2024-12-30
+    #[default]
+    Iso,
+    /// Calendar year of the Monday, e.g. `2024/Week 53` from Monday
+    /// 2024-12-30
+    Calendar,
+}
+
+/// A week starting on the configured day, named after the ISO week it
+/// shares the most days with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Week {
+    iso: IsoWeek,
+    start: WeekStart,
+    year: WeekYear,
+    style: NameStyle,
+}
+
+impl Week {
+    pub fn new(date: NaiveDate, start: WeekStart) -> Self {
+        let iso = match start {
+            WeekStart::Monday => date.iso_week(),
+            WeekStart::Sunday => (date + Days::new(1)).iso_week(),
+        };
+        Self::from_iso(iso, start)
+    }
+
+    pub fn from_iso(iso: IsoWeek, start: WeekStart) -> Self {
+        Self {
+            iso,
+            start,
+            year: WeekYear::default(),
+            style: NameStyle::default(),
+        }
+    }
+
+    /// The week named after `year` and `number` with `week_year`, the
+    /// inverse of [`Week::year_number`]
+    pub fn from_year_number(
+        year: i32,
+        number: u32,
+        start: WeekStart,
+        week_year: WeekYear,
+    ) -> Option<Self> {
+        // the last week of a calendar year may be the first ISO week of the
+        // next one
+        [(year, number), (year + 1, 1)]
+            .into_iter()
+            .filter_map(|(year, number)| NaiveDate::from_isoywd_opt(year, number, Weekday::Mon))
+            .map(|monday| Self::from_iso(monday.iso_week(), start).week_year(week_year))
+            .find(|week| week.year_number() == (year, number))
+    }
+
+    pub fn week_year(mut self, year: WeekYear) -> Self {
+        self.year = year;
+        self
+    }
+
+    pub fn name_style(mut self, style: NameStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn style(&self) -> NameStyle {
+        self.style
+    }
+
+    pub fn iso(&self) -> IsoWeek {
+        self.iso
+    }
+
+    /// Year and number of the week in its name: the ISO ones, but the first
+    /// ISO week starting in December is the last week of the calendar year
+    /// with `WeekYear::Calendar`
+    pub fn year_number(&self) -> (i32, u32) {
+        let iso = self.iso();
+        let monday = NaiveDate::from_isoywd_opt(iso.year(), iso.week(), Weekday::Mon)
+            .expect("IsoWeek is valid");
+
+        match self.year {
+            WeekYear::Calendar if monday.year() < iso.year() => {
+                let last = NaiveDate::from_ymd_opt(monday.year(), 12, 28).unwrap();
+                (monday.year(), last.iso_week().week() + 1)
+            }
+            _ => (iso.year(), iso.week()),
         }
     }
 }
@@ -85,6 +369,19 @@ impl DateRange for IsoWeek {
         NaiveDate::from_isoywd_opt(self.year(), self.week(), Weekday::Sun).unwrap()
     }
 }
+impl DateRange for Week {
+    type Element = NaiveDate;
+
+    fn first(&self) -> NaiveDate {
+        match self.start {
+            WeekStart::Monday => self.iso.first(),
+            WeekStart::Sunday => self.iso.first() - Days::new(1),
+        }
+    }
+    fn last(&self) -> NaiveDate {
+        self.first() + Days::new(6)
+    }
+}
 impl DateRange for Month {
     type Element = NaiveDate;
 
@@ -95,6 +392,35 @@ impl DateRange for Month {
         self.first() + Months::new(1) - Days::new(1)
     }
 }
+impl DateRange for Quarter {
+    type Element = Month;
+
+    fn first(&self) -> Month {
+        Month {
+            year: self.year,
+            month: self.quarter * 3 - 2,
+            ..Default::default()
+        }
+    }
+    fn last(&self) -> Month {
+        Month {
+            year: self.year,
+            month: self.quarter * 3,
+            ..Default::default()
+        }
+    }
+}
+impl DateRange for Sprint {
+    type Element = NaiveDate;
+
+    fn first(&self) -> NaiveDate {
+        let offset = self.index * i64::from(self.cycle.length);
+        self.cycle.anchor + chrono::TimeDelta::days(offset)
+    }
+    fn last(&self) -> NaiveDate {
+        self.first() + Days::new(u64::from(self.cycle.length) - 1)
+    }
+}
 impl DateRange for Year {
     type Element = Month;
 
@@ -102,12 +428,14 @@ impl DateRange for Year {
         Month {
             year: self.0,
             month: 1,
+            ..Default::default()
         }
     }
     fn last(&self) -> Month {
         Month {
             year: self.0,
             month: 12,
+            ..Default::default()
         }
     }
 }
@@ -135,6 +463,30 @@ impl Navigation for Month {
     }
 }
 
+impl Navigation for Quarter {
+    fn next(&self) -> Self {
+        Quarter::from(self.last().next())
+    }
+    fn prev(&self) -> Self {
+        Quarter::from(self.first().prev())
+    }
+}
+
+impl Navigation for Sprint {
+    fn next(&self) -> Self {
+        Self {
+            index: self.index + 1,
+            ..*self
+        }
+    }
+    fn prev(&self) -> Self {
+        Self {
+            index: self.index - 1,
+            ..*self
+        }
+    }
+}
+
 impl Navigation for Year {
     fn next(&self) -> Self {
         Year(self.0 + 1)
@@ -153,6 +505,21 @@ impl Navigation for IsoWeek {
     }
 }
 
+impl Navigation for Week {
+    fn next(&self) -> Self {
+        Self {
+            iso: self.iso.next(),
+            ..*self
+        }
+    }
+    fn prev(&self) -> Self {
+        Self {
+            iso: self.iso.prev(),
+            ..*self
+        }
+    }
+}
+
 pub struct DateIterator<'a, T, U>
 where
     T: DateRange<Element = U> + ?Sized,
@@ -195,6 +562,86 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn timestamp() {
+        use chrono::TimeZone;
+
+        let now = Utc.with_ymd_and_hms(2024, 7, 1, 10, 0, 0).unwrap();
+        assert_eq!("2024-07-01T10:00:00Z", super::timestamp(now, None));
+        assert_eq!(
+            "2024-07-01T12:00:00+02:00",
+            super::timestamp(now, Some(chrono_tz::Europe::Paris))
+        );
+    }
+
+    #[test]
+    fn rest_of() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(
+            (date(2024, 2, 14), date(2024, 2, 29)),
+            rest_of_month(date(2024, 2, 14))
+        );
+        assert_eq!(
+            (date(2024, 9, 30), date(2024, 9, 30)),
+            rest_of_month(date(2024, 9, 30))
+        );
+        assert_eq!(
+            (date(2024, 2, 14), date(2024, 12, 31)),
+            rest_of_year(date(2024, 2, 14))
+        );
+    }
+
+    #[test]
+    fn days_left() {
+        let days_left = |y, m, d| {
+            let date = NaiveDate::from_ymd_opt(y, m, d).unwrap();
+            (
+                super::days_left(date, Month::from(date).last()),
+                super::days_left(date, Year::from(y).last().last()),
+            )
+        };
+
+        assert_eq!((30, 365), days_left(2024, 1, 1));
+        assert_eq!((0, 335), days_left(2024, 1, 31));
+        assert_eq!((30, 30), days_left(2024, 12, 1));
+        assert_eq!((0, 0), days_left(2024, 12, 31));
+        assert_eq!((27, 58), days_left(2023, 11, 3));
+    }
+
+    #[test]
+    fn today() {
+        use chrono::TimeZone;
+
+        let today = |y, m, d, h, min, timezone: &str| {
+            super::today(
+                Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap(),
+                Some(timezone.parse().unwrap()),
+            )
+        };
+
+        // 00:30 EDT, the clocks go back to EST at 02:00: a fixed -5 offset
+        // would give the previous day
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 11, 3).unwrap(),
+            today(2024, 11, 3, 4, 30, "America/New_York")
+        );
+        // 23:30 EST on the same day
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 11, 3).unwrap(),
+            today(2024, 11, 4, 4, 30, "America/New_York")
+        );
+        // 00:30 CEST right after the spring forward in Paris
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(),
+            today(2024, 3, 31, 22, 30, "Europe/Paris")
+        );
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+            super::today(Utc.with_ymd_and_hms(2024, 3, 31, 23, 30, 0).unwrap(), None)
+        );
+    }
+
     #[test]
     fn month_arithmetic() {
         let month = Month::from(NaiveDate::from_ymd_opt(2024, 12, 1).unwrap());
@@ -202,7 +649,8 @@ mod tests {
         assert_eq!(
             Month {
                 year: 2025,
-                month: 1
+                month: 1,
+                ..Default::default()
             },
             month + Months::new(1)
         );
@@ -210,12 +658,146 @@ mod tests {
         assert_eq!(
             Month {
                 year: 2023,
-                month: 12
+                month: 12,
+                ..Default::default()
             },
             month - Months::new(12)
         );
     }
 
+    #[test]
+    fn week_start() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 9, d).unwrap();
+
+        // Sunday 2024-09-01 to Saturday 2024-09-07
+        let sunday = Week::new(date(1), WeekStart::Sunday);
+        for d in 1..=7 {
+            assert_eq!(sunday, Week::new(date(d), WeekStart::Sunday));
+        }
+        assert_ne!(sunday, Week::new(date(8), WeekStart::Sunday));
+        assert_eq!(36, sunday.iso().week());
+        assert_eq!(date(1), sunday.first());
+        assert_eq!(date(7), sunday.last());
+
+        // Monday 2024-09-02 to Sunday 2024-09-08
+        let monday = Week::new(date(2), WeekStart::Monday);
+        for d in 2..=8 {
+            assert_eq!(monday, Week::new(date(d), WeekStart::Monday));
+        }
+        assert_ne!(monday, Week::new(date(1), WeekStart::Monday));
+        assert_eq!(36, monday.iso().week());
+        assert_eq!(date(2), monday.first());
+        assert_eq!(date(8), monday.last());
+
+        // Sunday 2024-12-29 starts the first week of 2025
+        let week = Week::new(
+            NaiveDate::from_ymd_opt(2024, 12, 29).unwrap(),
+            WeekStart::Sunday,
+        );
+        assert_eq!((2025, 1), (week.iso().year(), week.iso().week()));
+        assert_eq!(
+            week.prev().last(),
+            NaiveDate::from_ymd_opt(2024, 12, 28).unwrap()
+        );
+        assert_eq!(7, week.iter().count());
+    }
+
+    #[test]
+    fn week_start_from_locale() {
+        let locale = |vars: &[(&str, &str)]| {
+            let vars = vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<std::collections::HashMap<_, _>>();
+            WeekStart::from_locale(|name| vars.get(name).cloned())
+        };
+
+        assert_eq!(WeekStart::Sunday, locale(&[("LANG", "en_US.UTF-8")]));
+        assert_eq!(WeekStart::Monday, locale(&[("LANG", "en_GB.UTF-8")]));
+        assert_eq!(WeekStart::Monday, locale(&[("LANG", "fr_FR@euro")]));
+        assert_eq!(WeekStart::Sunday, locale(&[("LANG", "ja_JP")]));
+        assert_eq!(
+            WeekStart::Monday,
+            locale(&[("LANG", "en_US.UTF-8"), ("LC_TIME", "de_DE.UTF-8")])
+        );
+        assert_eq!(
+            WeekStart::Sunday,
+            locale(&[("LC_ALL", "en_CA.UTF-8"), ("LC_TIME", "de_DE.UTF-8")])
+        );
+        assert_eq!(
+            WeekStart::Sunday,
+            locale(&[("LC_ALL", ""), ("LANG", "pt_BR.UTF-8")])
+        );
+        assert_eq!(WeekStart::Monday, locale(&[("LANG", "C")]));
+        assert_eq!(WeekStart::Monday, locale(&[]));
+    }
+
+    #[test]
+    fn week_year() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let week = |y, m, d, year| Week::new(date(y, m, d), WeekStart::Monday).week_year(year);
+
+        // Monday 2024-12-30 is in the first ISO week of 2025
+        assert_eq!((2025, 1), week(2024, 12, 30, WeekYear::Iso).year_number());
+        assert_eq!((2025, 1), week(2025, 1, 5, WeekYear::Iso).year_number());
+        assert_eq!(
+            (2024, 53),
+            week(2024, 12, 30, WeekYear::Calendar).year_number()
+        );
+        assert_eq!(
+            (2024, 53),
+            week(2025, 1, 5, WeekYear::Calendar).year_number()
+        );
+        assert_eq!(
+            (2025, 2),
+            week(2024, 12, 30, WeekYear::Calendar).next().year_number()
+        );
+        assert_eq!(
+            (2024, 52),
+            week(2024, 12, 30, WeekYear::Calendar).prev().year_number()
+        );
+
+        // Monday 2021-01-04 starts the first ISO week of 2021, after the
+        // 53rd of 2020
+        assert_eq!(
+            (2020, 53),
+            week(2021, 1, 1, WeekYear::Calendar).year_number()
+        );
+        assert_eq!(
+            (2021, 1),
+            week(2021, 1, 4, WeekYear::Calendar).year_number()
+        );
+        // 2020-12-28 is in the 53rd week of 2020: the ISO week-year already
+        // is the calendar year
+        assert_eq!(
+            (2020, 53),
+            week(2020, 12, 31, WeekYear::Calendar).year_number()
+        );
+
+        // Sunday 2024-12-29 starts the week of Monday 2024-12-30
+        let sunday = Week::new(date(2024, 12, 29), WeekStart::Sunday).week_year(WeekYear::Calendar);
+        assert_eq!((2024, 53), sunday.year_number());
+
+        // and back from the names
+        let named = |year, number, week_year| {
+            Week::from_year_number(year, number, WeekStart::Monday, week_year)
+                .map(|week| week.first())
+        };
+        assert_eq!(Some(date(2024, 12, 30)), named(2025, 1, WeekYear::Iso));
+        assert_eq!(None, named(2024, 53, WeekYear::Iso));
+        assert_eq!(
+            Some(date(2024, 12, 30)),
+            named(2024, 53, WeekYear::Calendar)
+        );
+        assert_eq!(None, named(2025, 1, WeekYear::Calendar));
+        assert_eq!(Some(date(2025, 1, 6)), named(2025, 2, WeekYear::Calendar));
+        assert_eq!(
+            Some(date(2020, 12, 28)),
+            named(2020, 53, WeekYear::Calendar)
+        );
+        assert_eq!(Some(date(2021, 1, 4)), named(2021, 1, WeekYear::Calendar));
+    }
+
     mod date_range {
         use super::*;
 
@@ -245,26 +827,62 @@ mod tests {
             assert_eq!(
                 Month {
                     year: 2024,
-                    month: 11
+                    month: 11,
+                    ..Default::default()
                 },
                 month.prev()
             );
             assert_eq!(
                 Month {
                     year: 2025,
-                    month: 1
+                    month: 1,
+                    ..Default::default()
                 },
                 month.next()
             );
             assert_eq!(
                 Month {
                     year: 2025,
-                    month: 1
+                    month: 1,
+                    ..Default::default()
                 },
                 month.next()
             );
         }
 
+        #[test]
+        fn quarter() {
+            let quarter = Quarter::from(NaiveDate::from_ymd_opt(2024, 12, 1).unwrap());
+            assert_eq!(
+                Quarter {
+                    year: 2024,
+                    quarter: 4
+                },
+                quarter
+            );
+            assert_eq!(
+                Quarter {
+                    year: 2024,
+                    quarter: 3
+                },
+                quarter.prev()
+            );
+            assert_eq!(
+                Quarter {
+                    year: 2025,
+                    quarter: 1
+                },
+                quarter.next()
+            );
+            assert_eq!(
+                Quarter {
+                    year: 2024,
+                    quarter: 4
+                },
+                quarter.next().prev()
+            );
+        }
+
         #[test]
         fn year() {
             let year = Year::from(2024);
@@ -290,6 +908,36 @@ mod tests {
             assert_eq!(month.last(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
         }
 
+        #[test]
+        fn month_last() {
+            let days = |february| [31, february, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+            for (year, february) in [(2023, 28), (2024, 29), (1900, 28), (2000, 29)] {
+                for (month, day) in (1..=12).zip(days(february)) {
+                    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+                    assert_eq!(
+                        NaiveDate::from_ymd_opt(year, month, day).unwrap(),
+                        Month::from(first).last(),
+                        "{year}-{month}"
+                    );
+                }
+            }
+
+            let december = Month::from(NaiveDate::from_ymd_opt(2024, 12, 15).unwrap());
+            assert_eq!(
+                NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+                december.last()
+            );
+            assert_eq!(
+                NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                december.last().next()
+            );
+            assert_eq!(
+                NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+                december.next().last()
+            );
+        }
+
         #[test]
         fn year() {
             let year = Year::from(2024);
@@ -319,10 +967,56 @@ mod tests {
             assert_eq!(29, month.iter().count());
         }
 
+        #[test]
+        fn quarter() {
+            let quarter = Quarter::from(NaiveDate::from_ymd_opt(2024, 8, 5).unwrap());
+            assert_eq!(
+                vec!["July", "August", "September"],
+                quarter
+                    .iter()
+                    .map(|m| m.name().to_owned())
+                    .collect::<Vec<_>>()
+            );
+        }
+
         #[test]
         fn year() {
             let year = Year::from(2024);
             assert_eq!(12, year.iter().count());
         }
     }
+
+    #[test]
+    fn sprint() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let cycle = SprintCycle {
+            anchor: date(1, 1),
+            length: 14,
+        };
+
+        // the anchor starts the first sprint, the last day ending it
+        assert_eq!(1, cycle.sprint(date(1, 1)).number());
+        assert_eq!(1, cycle.sprint(date(1, 14)).number());
+        let sprint = cycle.sprint(date(1, 15));
+        assert_eq!(2, sprint.number());
+        assert_eq!(date(1, 15), sprint.first());
+        assert_eq!(date(1, 28), sprint.last());
+        assert_eq!(14, sprint.iter().count());
+        assert_eq!(Some(date(1, 28)), sprint.iter().last());
+        assert_eq!(sprint, cycle.numbered(2));
+
+        assert_eq!(date(1, 29), sprint.next().first());
+        assert_eq!(cycle.sprint(date(1, 2)), sprint.prev());
+        assert_eq!(sprint, sprint.next().prev());
+
+        // before the anchor
+        let sprint = cycle.sprint(date(1, 1).prev());
+        assert_eq!(0, sprint.number());
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 12, 18).unwrap(),
+            sprint.first()
+        );
+        assert_eq!(date(1, 1).prev(), sprint.last());
+        assert_eq!(-1, cycle.sprint(date(1, 1) - Days::new(15)).number());
+    }
 }
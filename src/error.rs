@@ -0,0 +1,114 @@
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// Failures of the preparation that callers may want to tell apart, e.g.
+/// to retry on IO errors but bail on configuration errors
+#[derive(Debug, thiserror::Error)]
+pub enum PrepareError {
+    #[error("{path:?} is not a logseq graph, it has neither journals nor pages, see --flat")]
+    NotAGraph { path: PathBuf },
+
+    #[error("--from {from} should not be after --to {to}")]
+    DateRangeInvalid { from: NaiveDate, to: NaiveDate },
+
+    #[error("Accessing file {path:?}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{}line {line}: {message} in {text:?}", in_file(path))]
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        text: String,
+        message: String,
+    },
+
+    #[error("{path:?} exists and is not up to date, --no-clobber refuses to modify it")]
+    PageExists { path: PathBuf },
+
+    #[error("Invalid format {format:?}: {reason}")]
+    FormatInvalid { format: String, reason: String },
+
+    #[error("Not confirmed to {action}, --yes proceeds without asking")]
+    NotConfirmed { action: String },
+}
+
+fn in_file(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|path| format!("{path:?} "))
+        .unwrap_or_default()
+}
+
+impl PrepareError {
+    /// Parse error of `text`, the first line of the parsed text
+    pub fn parse<M: Into<String>>(text: &str, message: M) -> Self {
+        Self::Parse {
+            path: None,
+            line: 1,
+            text: text.to_owned(),
+            message: message.into(),
+        }
+    }
+
+    pub fn format_invalid<R: Into<String>>(format: &str, reason: R) -> Self {
+        Self::FormatInvalid {
+            format: format.to_owned(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Locate a parse error of a text found `offset` lines into its file
+    pub fn at_line(self, offset: usize) -> Self {
+        match self {
+            Self::Parse {
+                path,
+                line,
+                text,
+                message,
+            } => Self::Parse {
+                path,
+                line: line + offset,
+                text,
+                message,
+            },
+            error => error,
+        }
+    }
+
+    /// Locate a parse error in the page at `path`
+    pub fn in_file(self, file: &Path) -> Self {
+        match self {
+            Self::Parse {
+                line,
+                text,
+                message,
+                ..
+            } => Self::Parse {
+                path: Some(file.to_path_buf()),
+                line,
+                text,
+                message,
+            },
+            error => error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let error = PrepareError::parse("filters", "Can't find ::").at_line(2);
+        assert_eq!(r#"line 3: Can't find :: in "filters""#, error.to_string());
+
+        let error = error.in_file(Path::new("journals/2024_09_01.md"));
+        assert_eq!(
+            r#""journals/2024_09_01.md" line 3: Can't find :: in "filters""#,
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::Context;
+use chrono::NaiveDate;
+use std::path::Path;
+
+/// Days with a commit in the git repository at `repo`, by author date in
+/// the time zone of the author, sorted and without duplicates. Runs the
+/// `git` command, 2.25 or later for `%as`
+pub fn dates(repo: &Path) -> anyhow::Result<Vec<NaiveDate>> {
+    let output = match std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "--format=%as"])
+        .output()
+    {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            anyhow::bail!("--from-git needs the git command, 2.25 or later, which isn't installed")
+        }
+        output => output.with_context(|| format!("running git log in {:?}", repo))?,
+    };
+    if !output.status.success() {
+        // git log fails on a repository without commits yet
+        let repository = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["rev-parse", "--git-dir"])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        let commits = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if repository && !commits {
+            return Ok(Vec::new());
+        }
+        anyhow::bail!(
+            "git log failed in {:?}: {}",
+            repo,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let mut dates = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            line.parse::<NaiveDate>()
+                .with_context(|| format!("reading the commit date {line:?}"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    dates.sort();
+    dates.dedup();
+    Ok(dates)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{graph, preparer};
+    use anyhow::Result;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    #[ignore = "needs the git command, 2.25 or later, run with cargo test -- --ignored"]
+    fn from_git() -> Result<()> {
+        let temp_dir = graph()?;
+        let repo = TempDir::new()?;
+        let git = |args: &[&str], date: &str| -> Result<()> {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .status()?;
+            assert!(status.success(), "git {args:?}");
+            Ok(())
+        };
+        git(&["init", "-q"], "")?;
+        let args = ["--from-git", repo.path().to_str().unwrap()];
+        // no commits yet
+        preparer(&temp_dir, &args)?.run()?;
+        assert_eq!(0, std::fs::read_dir(temp_dir.child("journals"))?.count());
+
+        // by the date of the author, in their time zone
+        for date in [
+            "2024-09-03T10:00:00+02:00",
+            "2024-09-03T18:00:00+02:00",
+            "2024-09-05T23:30:00-07:00",
+            "2024-09-17T09:00:00+00:00",
+        ] {
+            git(&["commit", "-q", "--allow-empty", "-m", date], date)?;
+        }
+
+        preparer(&temp_dir, &args)?.run()?;
+
+        let journals = std::fs::read_dir(temp_dir.child("journals"))?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<std::collections::BTreeSet<_>>>()?;
+        assert_eq!(
+            vec!["2024_09_03.md", "2024_09_05.md", "2024_09_17.md"],
+            journals.into_iter().collect::<Vec<_>>()
+        );
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___September.md"))?;
+        assert!(month.contains("[[2024-09-05]]") && !month.contains("[[2024-09-06]]"));
+        // no commit that week
+        assert!(!temp_dir.child("pages/2024___Week 37.md").exists());
+        assert!(temp_dir.child("pages/2024___Week 38.md").exists());
+
+        // within --from and --to
+        let args = [&args[..], &["--from", "2024-09-04", "--check"]].concat();
+        let preparation = preparer(&temp_dir, &args)?;
+        assert_eq!(
+            ("2024-09-04".parse()?, "2024-09-17".parse()?),
+            (preparation.from, preparation.to)
+        );
+
+        assert!(preparer(
+            &temp_dir,
+            &["--from-git", temp_dir.path().to_str().unwrap()]
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
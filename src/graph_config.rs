@@ -0,0 +1,207 @@
+use crate::date_utils::WeekStart;
+use crate::error::PrepareError;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The settings of logseq's `logseq/config.edn` that apply to preparing
+/// the journal, the other ones being skipped
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GraphConfig {
+    /// `:start-of-week`, 0 being Monday and 6 Sunday
+    pub week_start: Option<WeekStart>,
+    /// `:feature/enable-journals?`, false for graphs without journals
+    pub journals: Option<bool>,
+    /// `:meta/version`, the version of the config format
+    pub version: Option<u32>,
+}
+
+impl GraphConfig {
+    pub fn path(graph: &Path) -> PathBuf {
+        graph.join("logseq").join("config.edn")
+    }
+
+    /// Read the config of `graph`, if any. Unless `strict`, a config that
+    /// can't be read is reported and ignored
+    pub fn read(graph: &Path, strict: bool) -> Result<Self> {
+        let path = Self::path(graph);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let config = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {:?}", path))
+            .and_then(|text| text.parse::<Self>().map_err(|e| e.in_file(&path).into()));
+        match config {
+            Ok(config) => Ok(config),
+            Err(e) if strict => Err(e),
+            Err(e) => {
+                log::warn!("Ignoring {}: {:#}", path.display(), e);
+                Ok(Self::default())
+            }
+        }
+    }
+}
+
+/// Scan the EDN text for the known keys, at any depth, skipping strings and
+/// comments
+impl FromStr for GraphConfig {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        let mut config = Self::default();
+        let lines = lines(s)?;
+        let mut tokens = lines.iter().enumerate().flat_map(|(index, line)| {
+            line.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|token| !token.is_empty())
+                .map(move |token| (index, token))
+        });
+
+        while let Some((_, token)) = tokens.next() {
+            let key = token.trim_start_matches(['{', '[', '(']);
+            if ![
+                ":start-of-week",
+                ":feature/enable-journals?",
+                ":meta/version",
+            ]
+            .contains(&key)
+            {
+                continue;
+            }
+
+            let (index, value) = tokens.next().unwrap_or((lines.len() - 1, ""));
+            let value = value.trim_end_matches(['}', ']', ')']);
+            let invalid = |expected: &str| {
+                PrepareError::parse(
+                    s.lines().nth(index).unwrap_or_default().trim(),
+                    format!("Unsupported {key} {value:?}, expected {expected}"),
+                )
+                .at_line(index)
+            };
+            if key == ":start-of-week" {
+                config.week_start = match value {
+                    "0" => Some(WeekStart::Monday),
+                    "6" => Some(WeekStart::Sunday),
+                    _ => return Err(invalid("0 or 6")),
+                };
+            } else if key == ":meta/version" {
+                config.version = Some(value.parse().map_err(|_| invalid("a number"))?);
+            } else {
+                config.journals = match value {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    _ => return Err(invalid("true or false")),
+                };
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Lines of `s` without their strings and comments, which could hold keys
+fn lines(s: &str) -> Result<Vec<String>, PrepareError> {
+    let mut lines = Vec::new();
+    let mut in_string = None;
+
+    for (index, line) in s.lines().enumerate() {
+        let mut code = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match (in_string, c) {
+                (Some(_), '\\') => {
+                    chars.next();
+                }
+                (Some(_), '"') => in_string = None,
+                (Some(_), _) => {}
+                (None, '"') => {
+                    in_string = Some(index);
+                    code.push(' ');
+                }
+                (None, ';') => break,
+                (None, c) => code.push(c),
+            }
+        }
+        lines.push(code);
+    }
+
+    match in_string {
+        Some(index) => Err(PrepareError::parse(
+            s.lines().nth(index).unwrap_or_default(),
+            "Unterminated string",
+        )
+        .at_line(index)),
+        None => Ok(lines),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse() -> anyhow::Result<()> {
+        let config = indoc! {r#"
+            {:meta/version 1
+             ;; :start-of-week 0
+             :default-templates {:journals ""}
+             :ui/custom-css "a { color: red } :start-of-week 3"
+             :commands [[:cmd "\"quoted\"" #{:a :b}]]
+             :journal/page-title-format "MMM do, yyyy"
+             :hidden [] :start-of-week 6, #_ :ignored
+             :macros {"poem" "Rose is $1, violet's $2."}}}}
+        "#}
+        .parse::<GraphConfig>()?;
+        assert_eq!(Some(WeekStart::Sunday), config.week_start);
+        assert_eq!(Some(1), config.version);
+
+        assert_eq!(GraphConfig::default(), "{}".parse()?);
+        assert_eq!(
+            Some(WeekStart::Monday),
+            "{:start-of-week\n 0}".parse::<GraphConfig>()?.week_start
+        );
+
+        let config =
+            "{:feature/enable-journals? false\n :start-of-week 0}".parse::<GraphConfig>()?;
+        assert_eq!(Some(false), config.journals);
+        assert_eq!(Some(WeekStart::Monday), config.week_start);
+        assert_eq!(None, GraphConfig::default().journals);
+
+        let error = "{:start-of-week 2}".parse::<GraphConfig>().unwrap_err();
+        assert!(matches!(error, PrepareError::Parse { line: 1, .. }));
+        let error = "{:feature/enable-journals? nil}"
+            .parse::<GraphConfig>()
+            .unwrap_err();
+        assert!(error.to_string().contains("true or false"), "{error}");
+        let error = "{:meta/version \"1\"}".parse::<GraphConfig>().unwrap_err();
+        assert!(error.to_string().contains("a number"), "{error}");
+        let error = "{\n:title \"unterminated\n}"
+            .parse::<GraphConfig>()
+            .unwrap_err();
+        assert!(matches!(error, PrepareError::Parse { line: 2, .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn read() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        assert_eq!(
+            GraphConfig::default(),
+            GraphConfig::read(temp_dir.path(), true)?
+        );
+
+        temp_dir
+            .child("logseq/config.edn")
+            .write_str("{:start-of-week \"monday\"}")?;
+        assert_eq!(
+            GraphConfig::default(),
+            GraphConfig::read(temp_dir.path(), false)?
+        );
+        assert!(GraphConfig::read(temp_dir.path(), true).is_err());
+
+        Ok(())
+    }
+}
@@ -1,43 +1,175 @@
 use anyhow::Result;
-use chrono::{Datelike, Days, IsoWeek, Months, NaiveDate, Utc, Weekday};
-use std::path::PathBuf;
+use chrono::{Datelike, Days, NaiveDate, Utc, Weekday};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 mod options;
 
 mod page;
-use page::Page;
+use page::{Indent, Page};
+
+mod page_cache;
+use page_cache::PageCache;
+
+mod confirm;
+use confirm::Confirm;
+
+mod error;
+use error::PrepareError;
 
 mod date_utils;
-use date_utils::{DateRange, Month, Navigation, Year};
+use date_utils::{
+    DateRange, Month, NameStyle, Navigation, Quarter, Sprint, SprintCycle, Week, WeekStart,
+    WeekYear, Year,
+};
+
+mod git;
+
+mod graph_config;
+use graph_config::GraphConfig;
+
+mod merge;
 
 mod metadata;
-use metadata::{Filters, ToMetadata};
+use metadata::{Filters, List, Metadata, ToMetadata, Value};
+
+mod migrate;
+
+mod normalize;
+
+mod plan_graph;
+
+mod preflight;
+
+mod prune;
+
+mod recurrence;
+use recurrence::Recurrence;
+
+mod retry;
+
+mod state;
+use state::State;
+
+mod stats;
+
+mod template;
+use template::{Template, TemplateContext};
+
+mod title;
+use title::TitleFormat;
 
 mod utils;
-use utils::{JournalName, ToEmbedded, ToLink};
+use utils::{
+    EmbeddedBlock, FromJournalName, JournalFormat, JournalName, NameFilter, SkipDates, ToEmbedded,
+    ToLink,
+};
+
+/// Process exit status, documented in the README
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Success = 0,
+    Failure = 1,
+    Usage = 2,
+    Unchanged = 3,
+    Invalid = 4,
+    Io = 5,
+    Stale = 6,
+    Clobber = 7,
+    Unconfirmed = 8,
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+impl Status {
+    /// The status of the run of `preparer`, which gave `result`
+    fn of(preparer: &Preparer, result: Result<Summary>) -> Self {
+        match result {
+            Ok(summary) if preparer.check && summary.updated > 0 => {
+                eprintln!("{} pages out of date", summary.updated);
+                Status::Stale
+            }
+            Ok(_) if preparer.check || preparer.plan_graph || preparer.show_config.is_some() => {
+                Status::Success
+            }
+            Ok(_) if preparer.preflight || preparer.stats => Status::Success,
+            Ok(_) if preparer.inspect.is_some() => Status::Success,
+            Ok(summary) if summary.updated == 0 => Status::Unchanged,
+            Ok(_) => Status::Success,
+            Err(e) if Self::is_invalid(&e) => Status::Invalid.fail(e),
+            Err(e) => Status::Failure.fail(e),
+        }
+    }
+
+    /// Whether `error` comes from reading an existing page, template or
+    /// format, e.g. with --preflight
+    fn is_invalid(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<PrepareError>(),
+                Some(PrepareError::Parse { .. } | PrepareError::FormatInvalid { .. })
+            )
+        })
+    }
+
+    fn fail(self, error: anyhow::Error) -> Self {
+        eprintln!("Error: {:?}", error);
+
+        if error.chain().any(|cause| cause.is::<std::io::Error>()) {
+            Status::Io
+        } else if matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::PageExists { .. })
+        ) {
+            Status::Clobber
+        } else if matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::NotConfirmed { .. })
+        ) {
+            Status::Unconfirmed
+        } else {
+            self
+        }
+    }
+}
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     use clap::error::ErrorKind::*;
-    use clap::Parser;
 
-    let cli = match options::Cli::try_parse_from(std::env::args_os()) {
+    let cli = match options::Cli::parse_with_config(std::env::args_os()) {
         Ok(cli) => cli,
         Err(e) => match e.kind() {
             DisplayHelp | DisplayVersion => {
                 println!("{}", e);
-                return Ok(());
+                return Status::Success.into();
             }
             _ => {
-                return Err(e.into());
+                return Status::Usage.fail(e.into()).into();
             }
         },
     };
 
-    setup_log(cli.verbose.log_level_filter())?;
+    if let Err(e) = setup_log(cli.verbose.log_level_filter()) {
+        return Status::Failure.fail(e).into();
+    }
+
+    if cli.list_profiles {
+        cli.profiles
+            .iter()
+            .for_each(|profile| println!("{profile}"));
+        return Status::Success.into();
+    }
 
-    Preparer::try_from(cli)?.run()?;
+    let preparer = match Preparer::try_from(cli) {
+        Ok(preparer) => preparer,
+        Err(e) => return Status::Usage.fail(e).into(),
+    };
 
-    Ok(())
+    Status::of(&preparer, preparer.run()).into()
 }
 
 fn setup_log(level: log::LevelFilter) -> Result<()> {
@@ -71,14 +203,110 @@ fn setup_log(level: log::LevelFilter) -> Result<()> {
     Ok(())
 }
 
+#[derive(Clone, Debug)]
 struct Preparer {
     pub from: NaiveDate,
     pub to: NaiveDate,
+    /// Months prepared instead of the whole range, with --months
+    pub months: Vec<Month>,
     pub path: PathBuf,
+    /// Journals and pages share the graph directory
+    pub flat: bool,
+    /// Whether the day pages are prepared, the graph's config can disable
+    /// the journals
+    pub journals: bool,
+    pub week_start: WeekStart,
+    pub week_year: WeekYear,
+    pub name_style: NameStyle,
     pub day_options: options::DayOptions,
+    pub day_case: options::DayCase,
     pub week_options: options::WeekOptions,
     pub month_options: options::MonthOptions,
+    pub quarter_options: options::QuarterOptions,
     pub year_options: options::YearOptions,
+    pub year_children: options::YearChildren,
+    pub sprints: Option<SprintCycle>,
+    pub hierarchy_style: options::HierarchyStyle,
+    pub day_order: options::DayOrder,
+    pub day_captions: bool,
+    pub max_embeds: Option<usize>,
+    pub embed_existing_only: bool,
+    pub month_week_index: bool,
+    pub month_layout: options::MonthLayout,
+    pub overview_footer: Option<Template>,
+    pub month_notes: Option<Template>,
+    pub bootstrap_block: Option<Template>,
+    /// Templates of the day pages of each day of week, with
+    /// --weekday-template
+    pub weekday_templates: Vec<(Weekday, Template)>,
+    pub seed_todos: Vec<(options::Period, Template)>,
+    pub schedule_blocks: Vec<options::Period>,
+    pub recurrences: Vec<Recurrence>,
+    pub mark_generated: bool,
+    pub emptied_blocks: page::Emptied,
+    pub collapse_embeds: bool,
+    pub embed_target: options::EmbedTarget,
+    pub countdown: bool,
+    pub compact: bool,
+    pub skip_future_weeks: bool,
+    /// Today in --timezone
+    pub today: NaiveDate,
+    /// Day embedded in the `Today` page, with --today-page
+    pub today_page: Option<NaiveDate>,
+    /// Pages read during the run
+    pub pages: PageCache,
+    pub preserve_meta: Vec<String>,
+    pub sort_lists: bool,
+    pub append_only: bool,
+    pub freeze_metadata: bool,
+    pub carry_tasks: bool,
+    pub day_week_footer: bool,
+    pub title_heading: bool,
+    pub on_this_day: Option<u32>,
+    pub created: Option<String>,
+    /// Value of `prepared-by::`, with --stamp-version
+    pub prepared_by: Option<String>,
+    pub restamp: bool,
+    pub indent: Option<Indent>,
+    pub bullet: Option<page::Bullet>,
+    pub block_spacing: Option<usize>,
+    pub final_newline: Option<bool>,
+    /// Whether to write the empty block first, with --leading-block auto
+    /// as told by the graph config
+    pub leading_block: bool,
+    pub io_retries: u32,
+    pub title_format: Option<TitleFormat>,
+    pub aliases: Vec<TitleFormat>,
+    pub title_separator: Option<String>,
+    pub skip_dates: SkipDates,
+    pub name_filters: Vec<NameFilter>,
+    pub journal_format: JournalFormat,
+    pub journal_extension: String,
+    pub journal_partition: Option<options::Partition>,
+    pub sidecar: Option<options::Sidecar>,
+    pub page_extension: String,
+    pub migrate_links: Option<(JournalFormat, JournalFormat)>,
+    pub prune: bool,
+    pub plan_graph: bool,
+    pub preflight: bool,
+    pub stats: bool,
+    pub links_only: bool,
+    /// Graph whose pages get merged into this one, with --merge-from
+    pub merge_from: Option<PathBuf>,
+    pub normalize: bool,
+    /// The effective options to print instead of preparing, with
+    /// --show-config
+    pub show_config: Option<Vec<String>>,
+    pub inspect: Option<PathBuf>,
+    pub resume: bool,
+    pub limit: Option<usize>,
+    pub no_clobber: bool,
+    pub keep_going: bool,
+    /// Whether --prune and --migrate-links may go on
+    pub confirm: Confirm,
+    pub dry_run: bool,
+    pub check: bool,
+    pub diff: bool,
 }
 
 impl TryFrom<options::Cli> for Preparer {
@@ -88,193 +316,3871 @@ impl TryFrom<options::Cli> for Preparer {
         options::Cli {
             to,
             from,
+            from_week,
+            horizon,
+            to_week,
+            months,
+            rest_of_month,
+            rest_of_year,
             path,
+            flat,
+            strict_config,
+            force,
+            timezone,
+            week_start,
+            week_year,
+            name_style,
             day,
+            day_case,
             week,
             month,
+            quarter,
             year,
+            year_children,
+            sprint_length,
+            sprint_anchor,
+            hierarchy_style,
+            day_order,
+            day_captions,
+            max_embeds,
+            embed_existing_only,
+            month_layout,
+            month_week_index,
+            overview_footer,
+            month_notes,
+            bootstrap_block,
+            weekday_template,
+            seed_todos,
+            schedule_blocks,
+            recurrence,
+            mark_generated,
+            emptied_blocks,
+            collapse_embeds,
+            embed_target,
+            countdown,
+            compact,
+            skip_future_weeks,
+            today_page,
+            preserve_meta,
+            sort_lists,
+            append_only,
+            freeze_metadata,
+            carry_tasks,
+            day_week_footer,
+            title_heading,
+            on_this_day,
+            stamp_created,
+            stamp_version,
+            restamp,
+            indent,
+            bullet,
+            block_spacing,
+            final_newline,
+            leading_block,
+            io_retries,
+            title_format,
+            aliases,
+            title_separator,
+            skip_dates_file,
+            from_git,
+            name_filter,
+            journal_format,
+            journal_extension,
+            journal_partition,
+            sidecar,
+            page_extension,
+            migrate_links,
+            from_format,
+            to_format,
+            prune,
+            plan_graph,
+            preflight,
+            stats,
+            links_only,
+            merge_from,
+            normalize,
+            show_config,
+            inspect,
+            resume,
+            limit,
+            no_clobber,
+            keep_going,
+            yes,
+            dry_run,
+            check,
+            diff,
+            effective,
             ..
         }: options::Cli,
     ) -> Result<Self> {
-        let from = from.unwrap_or(Utc::now().date_naive());
-        let to = to.unwrap_or(from + Months::new(1));
+        let today = date_utils::today(Utc::now(), timezone);
+        let git_dates = from_git.map(|repo| git::dates(&repo)).transpose()?;
+        if git_dates.as_ref().is_some_and(Vec::is_empty) {
+            log::warn!("No commits found by --from-git, skipping every day");
+        }
+        // with --months, the range spans them, each being prepared on its own
+        let span = months
+            .iter()
+            .map(|month| month.first())
+            .min()
+            .zip(months.iter().map(|month| month.last()).max());
+        let (from, to) = if let Some(span) = span {
+            span
+        } else if rest_of_month {
+            date_utils::rest_of_month(today)
+        } else if rest_of_year {
+            date_utils::rest_of_year(today)
+        } else {
+            let commits = git_dates.as_deref().unwrap_or_default();
+            let from = from
+                .or(from_week.map(|week| week.first()))
+                .or(commits.first().copied())
+                .unwrap_or(today);
+            let to = to
+                .or(to_week.map(|week| week.last()))
+                .or(commits.last().copied());
+            (from, to.unwrap_or_else(|| horizon.end(from)))
+        };
+
+        if to < from {
+            return Err(PrepareError::DateRangeInvalid { from, to }.into());
+        }
+        let split = path.join("journals").is_dir() || path.join("pages").is_dir();
+        let flat = flat || (!split && has_pages(&path, &[&journal_extension, &page_extension])?);
+        if !split && !flat {
+            return Err(PrepareError::NotAGraph { path }.into());
+        }
 
-        if to <= from {
-            anyhow::bail!("--from {} should be less than --to {}", from, to);
+        let journal_partition = journal_partition.filter(|_| {
+            if flat {
+                log::warn!("Flat graph, ignoring --journal-partition");
+            }
+            !flat
+        });
+
+        let graph_config = GraphConfig::read(&path, strict_config)?;
+        let journals = force || graph_config.journals != Some(false);
+        if !journals {
+            log::warn!(
+                "Journals are disabled in {}, skipping the day pages, --force prepares them",
+                GraphConfig::path(&path).display()
+            );
         }
 
+        let locale_week_start = || WeekStart::from_locale(|name| std::env::var(name).ok());
+        let week_start = match (week_start, graph_config.week_start) {
+            (Some(week_start), _) => week_start,
+            (None, Some(configured)) => {
+                // the versions before :start-of-week was read used the locale
+                let locale = locale_week_start();
+                if configured != locale {
+                    log::info!(
+                        "Starting the weeks on {:?}, the :start-of-week of {}, instead of {:?} from the locale",
+                        configured,
+                        GraphConfig::path(&path).display(),
+                        locale
+                    );
+                }
+                configured
+            }
+            (None, None) => locale_week_start(),
+        };
+
         Ok(Preparer {
             from,
             to,
+            months,
             path,
+            flat,
+            journals,
+            week_start,
+            week_year,
+            name_style,
             day_options: day.into(),
+            day_case,
             week_options: week.into(),
             month_options: month.into(),
+            quarter_options: quarter.into(),
             year_options: year.into(),
+            year_children,
+            sprints: sprint_length
+                .zip(sprint_anchor)
+                .map(|(length, anchor)| SprintCycle { anchor, length }),
+            hierarchy_style,
+            day_order,
+            day_captions,
+            max_embeds,
+            embed_existing_only,
+            month_layout,
+            month_week_index,
+            overview_footer: overview_footer
+                .map(|path| Template::read(&path))
+                .transpose()?,
+            month_notes: month_notes.map(|path| Template::read(&path)).transpose()?,
+            bootstrap_block: bootstrap_block
+                .map(|path| Template::read(&path))
+                .transpose()?,
+            weekday_templates: weekday_template
+                .into_iter()
+                .map(|(day, path)| Ok((day, Template::read(&path)?)))
+                .collect::<Result<_>>()?,
+            seed_todos: seed_todos
+                .into_iter()
+                .map(|(kind, path)| Ok((kind, Template::read(&path)?)))
+                .collect::<Result<_>>()?,
+            schedule_blocks,
+            recurrences: recurrence,
+            mark_generated,
+            emptied_blocks,
+            collapse_embeds,
+            embed_target,
+            countdown,
+            compact,
+            skip_future_weeks,
+            today_page: today_page.then_some(today),
+            today,
+            pages: PageCache::default(),
+            preserve_meta,
+            sort_lists,
+            append_only,
+            freeze_metadata,
+            carry_tasks,
+            day_week_footer,
+            title_heading,
+            on_this_day,
+            created: stamp_created.then(|| date_utils::timestamp(Utc::now(), timezone)),
+            prepared_by: stamp_version
+                .then(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+            restamp,
+            indent,
+            bullet,
+            block_spacing: block_spacing.map(usize::from),
+            final_newline,
+            leading_block: leading_block.writes(graph_config.version),
+            io_retries,
+            title_format,
+            aliases,
+            title_separator,
+            skip_dates: {
+                let mut skip_dates = skip_dates_file
+                    .map(|path| SkipDates::read(&path))
+                    .transpose()?
+                    .unwrap_or_default();
+                if let Some(dates) = &git_dates {
+                    skip_dates.except(dates);
+                }
+                skip_dates
+            },
+            name_filters: name_filter,
+            journal_format,
+            journal_extension,
+            journal_partition,
+            sidecar,
+            page_extension,
+            migrate_links: from_format.zip(to_format).filter(|_| migrate_links),
+            prune,
+            plan_graph,
+            preflight,
+            stats,
+            links_only,
+            merge_from,
+            normalize,
+            show_config: show_config.then_some(effective),
+            inspect,
+            resume,
+            limit,
+            no_clobber,
+            keep_going,
+            confirm: Confirm::new(yes),
+            dry_run,
+            check,
+            diff,
         })
     }
 }
 
+#[derive(Debug, Default)]
+struct Summary {
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+impl Summary {
+    fn record(&mut self, updated: bool) {
+        if updated {
+            self.updated += 1;
+        } else {
+            self.unchanged += 1;
+        }
+    }
+}
+
 impl Preparer {
-    fn run(&self) -> Result<()> {
+    fn run(&self) -> Result<Summary> {
+        if let Some(options) = &self.show_config {
+            options.iter().for_each(|line| println!("{line}"));
+            return Ok(Summary::default());
+        }
+        if let Some(path) = &self.inspect {
+            let page = Page::try_from(path.as_path())?;
+            print!("{}", page.inspect(|name| self.is_generated_name(name)));
+            return Ok(Summary::default());
+        }
+        if let Some((from, to)) = &self.migrate_links {
+            return self.migrate_links(from, to);
+        }
+        if self.prune {
+            return self.prune();
+        }
+        if self.normalize {
+            return self.normalize();
+        }
+        if let Some(source) = &self.merge_from {
+            return self.merge_graphs(source);
+        }
+        if self.plan_graph {
+            print!("{}", self.plan_graph());
+            return Ok(Summary::default());
+        }
+        if self.preflight {
+            return self.preflight();
+        }
+        if self.stats {
+            print!("{}", self.stats()?);
+            return Ok(Summary::default());
+        }
+
+        if !self.months.is_empty() {
+            return self.prepare_months();
+        }
+
         log::debug!("Preparing journal {:?}", self.path.display());
         log::debug!("from {} to {}", self.from, self.to);
         log::debug!("{}", self.day_options);
         log::debug!("{}", self.week_options);
         log::debug!("{}", self.month_options);
+        log::debug!("{}", self.quarter_options);
         log::debug!("{}", self.year_options);
 
-        let mut date: NaiveDate = self.from;
+        let mut date: NaiveDate = self.resume_from()?;
         let mut year = Year::from(date.year());
-        let mut month = Month::from(date);
-        let mut week = date.iso_week();
+        let mut quarter = Quarter::from(date);
+        let mut month = self.month(date);
+        let mut week = self.week(date);
+        let mut sprint = self.sprint(date);
+        let mut summary = Summary::default();
+
+        if let Some(today) = self.today_page.filter(|_| !self.links_only) {
+            summary.record(self.print_today(today)?);
+        }
+        summary.record(self.print_date(date)?);
+        summary.record(self.print_week(week)?);
+        if let Some(sprint) = sprint {
+            summary.record(self.print_sprint(sprint)?);
+        }
+        summary.record(self.print_month(month)?);
+        if self.month_notes.is_some() {
+            summary.record(self.print_month_notes(month)?);
+        }
+        if self.quarters() {
+            summary.record(self.print_quarter(quarter)?);
+        }
+        summary.record(self.print_year(year)?);
+        self.save_progress(date)?;
 
-        self.print_date(date)?;
-        self.print_week(week)?;
-        self.print_month(month)?;
-        self.print_year(year)?;
+        while date < self.to {
+            if let Some(limit) = self.limit.filter(|limit| summary.updated >= *limit) {
+                println!(
+                    "Limit of {} pages reached, continue with --from {}",
+                    limit,
+                    date + Days::new(1)
+                );
+                break;
+            }
 
-        loop {
             date = date + Days::new(1);
-            self.print_date(date)?;
+            summary.record(self.print_date(date)?);
 
-            let new_week = date.iso_week();
+            let new_week = self.week(date);
             if week != new_week {
-                self.print_week(new_week)?;
+                summary.record(self.print_week(new_week)?);
                 week = new_week;
             }
 
+            let new_sprint = self.sprint(date);
+            if let Some(new_sprint) = new_sprint.filter(|new| sprint != Some(*new)) {
+                summary.record(self.print_sprint(new_sprint)?);
+            }
+            sprint = new_sprint;
+
             let new_year = Year::from(date.year());
             if year != new_year {
-                self.print_year(new_year)?;
+                summary.record(self.print_year(new_year)?);
                 year = new_year;
             }
 
-            let new_month = Month::from(date);
+            let new_quarter = Quarter::from(date);
+            if quarter != new_quarter && self.quarters() {
+                summary.record(self.print_quarter(new_quarter)?);
+            }
+            quarter = new_quarter;
+
+            let new_month = self.month(date);
             if month != new_month {
-                self.print_month(new_month)?;
+                summary.record(self.print_month(new_month)?);
+                if self.month_notes.is_some() {
+                    summary.record(self.print_month_notes(new_month)?);
+                }
                 month = new_month;
             }
 
-            if date >= self.to {
-                break;
+            self.save_progress(date)?;
+        }
+
+        if date >= self.to && self.writes() {
+            State::remove(&self.path)?;
+        }
+
+        log::info!(
+            "{} pages updated, {} unchanged",
+            summary.updated,
+            summary.unchanged
+        );
+        log::debug!("{} pages read", self.pages.reads());
+        Ok(summary)
+    }
+
+    /// Prepare each of --months as its own range
+    fn prepare_months(&self) -> Result<Summary> {
+        let mut summary = Summary::default();
+
+        for month in &self.months {
+            let month = Preparer {
+                from: month.first(),
+                to: month.last(),
+                months: Vec::new(),
+                ..self.clone()
             }
+            .run()?;
+            summary.updated += month.updated;
+            summary.unchanged += month.unchanged;
         }
-        Ok(())
+
+        Ok(summary)
+    }
+
+    /// Whether the run writes the pages
+    fn writes(&self) -> bool {
+        !self.dry_run && !self.check
+    }
+
+    /// Record that every page of `date` was written, to resume from there
+    fn save_progress(&self, date: NaiveDate) -> Result<()> {
+        if !self.writes() {
+            return Ok(());
+        }
+
+        State {
+            from: self.from,
+            to: self.to,
+            done: date,
+        }
+        .write(&self.path)
+    }
+
+    /// First day to prepare: with --resume, the day after the last one
+    /// written by an interrupted run of the same range, or that day again if
+    /// its pages are not as expected
+    fn resume_from(&self) -> Result<NaiveDate> {
+        if !self.resume {
+            return Ok(self.from);
+        }
+        let Some(state) = State::read(&self.path)?
+            .filter(|state| state.from == self.from && state.to == self.to && state.done < self.to)
+        else {
+            log::info!("No interrupted run to resume, preparing the whole range");
+            return Ok(self.from);
+        };
+
+        let verifier = Preparer {
+            check: true,
+            diff: false,
+            ..self.clone()
+        };
+        let date = state.done;
+        let mut stale = verifier.print_date(date)?;
+        stale |= verifier.print_week(self.week(date))?;
+        if let Some(sprint) = self.sprint(date) {
+            stale |= verifier.print_sprint(sprint)?;
+        }
+        stale |= verifier.print_month(self.month(date))?;
+        if self.month_notes.is_some() {
+            stale |= verifier.print_month_notes(self.month(date))?;
+        }
+        if self.quarters() {
+            stale |= verifier.print_quarter(Quarter::from(date))?;
+        }
+        stale |= verifier.print_year(Year::from(date.year()))?;
+
+        if stale {
+            log::info!("Resuming on {}, its pages are not up to date", date);
+            Ok(date)
+        } else {
+            log::info!("Resuming after {}", date);
+            Ok(date + Days::new(1))
+        }
+    }
+
+    /// First and last days of the overview page named `name`
+    fn overview_bounds(&self, name: &str) -> Option<(NaiveDate, NaiveDate)> {
+        name.strip_suffix(".md")
+            .and_then(|name| utils::week_year_number(&name.replacen("___", "/", 1)))
+            .and_then(|(year, number)| {
+                Week::from_year_number(year, number, self.week_start, self.week_year)
+            })
+            .map(|week| (week.first(), week.last()))
+            .or_else(|| {
+                Month::from_journal_path_name(name).map(|month| (month.first(), month.last()))
+            })
+            .or_else(|| {
+                Quarter::from_journal_path_name(name)
+                    .map(|quarter| (quarter.first().first(), quarter.last().last()))
+            })
+            .or_else(|| {
+                Year::from_journal_path_name(name)
+                    .map(|year| (year.first().first(), year.last().last()))
+            })
+            .or_else(|| {
+                let number = name.strip_suffix(".md")?.strip_prefix("Sprint ")?;
+                let sprint = self.sprints?.numbered(number.parse().ok()?);
+                (sprint.to_journal_path_name() == name).then(|| (sprint.first(), sprint.last()))
+            })
+    }
+
+    /// Whether `name` is the one of a day or period page this tool generates
+    /// with the current options, to tell its links from the ones written by
+    /// hand on the pages without the generated marker
+    fn is_generated_name(&self, name: &str) -> bool {
+        let untitled = match &self.title_separator {
+            Some(separator) => name.replacen(separator.as_str(), "/", 1),
+            None => name.to_owned(),
+        };
+        let is_notes = |name: &str| {
+            self.month_notes.is_some()
+                && name
+                    .strip_suffix(" Notes")
+                    .and_then(Month::from_journal_name)
+                    .is_some()
+        };
+
+        self.journal_format.parse(name).is_some()
+            || [name, untitled.as_str()].into_iter().any(|name| {
+                self.overview_bounds(&format!("{}.md", name.replacen('/', "___", 1)))
+                    .is_some()
+            })
+            || is_notes(name)
     }
 
-    fn print_year(&self, year: Year) -> Result<()> {
-        self.update_page(self.page_path(year.to_journal_path_name()), |mut page| {
-            page.push_metadata(Filters::default().push(year.to_string(), false));
+    /// Whether everything on the page named `name`, the day page of `date`
+    /// if any, was generated by this tool with the current options
+    fn is_generated_page(&self, page: &Page, name: &str, date: Option<NaiveDate>) -> bool {
+        page.is_generated_only(
+            |name| self.is_generated_name(name),
+            |metadata| self.generates_metadata(metadata, name, date),
+        )
+    }
 
-            if self.year_options.nav {
-                page.push_metadata(year.next().to_link().to_metadata("next"));
-                page.push_metadata(year.prev().to_link().to_metadata("prev"));
+    /// Whether the current options give `metadata`, of a key also written
+    /// by hand like `title::`, to the page named `name`, the day page of
+    /// `date` if any
+    fn generates_metadata(&self, metadata: &Metadata, name: &str, date: Option<NaiveDate>) -> bool {
+        match (metadata.key.as_str(), &metadata.value, date) {
+            ("title", Value::Text(title), Some(date)) => self
+                .title_format
+                .as_ref()
+                .is_some_and(|format| format.format(date) == *title),
+            ("title", Value::Text(title), None) => self.titled(name).as_ref() == Some(title),
+            ("alias", Value::List(list), Some(date)) => {
+                let aliases = self
+                    .aliases
+                    .iter()
+                    .map(|format| format.format(date))
+                    .collect::<Vec<_>>();
+                !list.is_empty() && list.names().all(|name| aliases.iter().any(|a| a == name))
+            }
+            ("events", Value::List(list), _) => {
+                !list.is_empty()
+                    && list
+                        .names()
+                        .all(|name| self.recurrences.iter().any(|r| r.name == name))
             }
+            // the time of the run that created the page
+            ("created", Value::Text(created), _) => {
+                self.created.is_some() && chrono::DateTime::parse_from_rfc3339(created).is_ok()
+            }
+            _ => false,
+        }
+    }
 
-            for month in year.iter() {
-                page.push_content(month.to_link());
+    /// Title of the namespaced overview page `name` with --title-separator,
+    /// e.g. `2024 · September` for `2024/September`
+    fn titled(&self, name: &str) -> Option<String> {
+        let separator = self.title_separator.as_deref()?;
+        let path_name = format!("{}.md", name.replacen('/', "___", 1));
+        self.overview_bounds(&path_name)?;
+        name.contains('/').then(|| name.replacen('/', separator, 1))
+    }
+
+    /// Paths of every page covered by the range, journals and overviews
+    fn page_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        for date in self.from.iter_days().take_while(|date| *date <= self.to) {
+            let quarter = self
+                .quarters()
+                .then(|| self.page_path(Quarter::from(date).to_journal_path_name()));
+            for path in [
+                Some(self.journal_path(date)),
+                Some(self.page_path(self.week(date).to_journal_path_name())),
+                Some(self.page_path(self.month(date).to_journal_path_name())),
+                quarter,
+                Some(self.page_path(Year::from(date.year()).to_journal_path_name())),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
             }
+        }
 
-            Ok(page)
+        paths
+    }
+
+    /// Whether the page named `name` matches --name-filter, if any
+    fn is_selected(&self, name: &str) -> bool {
+        self.name_filters.is_empty() || self.name_filters.iter().any(|f| f.matches(name))
+    }
+
+    fn week(&self, date: NaiveDate) -> Week {
+        Week::new(date, self.week_start)
+            .week_year(self.week_year)
+            .name_style(self.name_style)
+    }
+
+    fn month(&self, date: NaiveDate) -> Month {
+        Month::from(date).name_style(self.name_style)
+    }
+
+    /// The sprint holding `date`, with --sprint-length
+    fn sprint(&self, date: NaiveDate) -> Option<Sprint> {
+        Some(self.sprints?.sprint(date))
+    }
+
+    fn print_year(&self, year: Year) -> Result<bool> {
+        if !self.is_selected(&year.to_journal_name()) {
+            return Ok(false);
+        }
+
+        self.update_overview(self.page_path(year.to_journal_path_name()), |page| {
+            self.year_page(year, page)
         })
     }
 
-    fn print_month(&self, month: Month) -> Result<()> {
-        self.update_page(self.page_path(month.to_journal_path_name()), |mut page| {
-            page.push_metadata(Filters::default().push("month", false));
+    fn year_page(&self, year: Year, mut page: Page) -> Result<Page> {
+        page.push_metadata(Filters::default().push(year.to_string(), false));
 
-            if self.month_options.nav {
-                page.push_metadata(month.next().to_link().to_metadata("next"));
-                page.push_metadata(month.prev().to_link().to_metadata("prev"));
-            }
+        if self.year_options.nav {
+            page.push_metadata(year.next().to_link().to_metadata("next"));
+            page.push_metadata(year.prev().to_link().to_metadata("prev"));
+        }
 
-            for date in month.iter() {
-                page.push_content(date.to_link().into_embedded());
+        if self.quarters() {
+            let quarters =
+                std::iter::successors(Some(Quarter::from(year.first())), |q| Some(q.next()));
+            for quarter in quarters.take(4) {
+                page.push_content(quarter.to_link());
             }
+        } else {
+            for month in year.iter().map(|month| month.name_style(self.name_style)) {
+                page.push_content(month.to_link());
+            }
+        }
+
+        let context = TemplateContext::default()
+            .with("period", year.to_journal_name())
+            .with("prev", year.prev().to_journal_name())
+            .with("next", year.next().to_journal_name())
+            .with("year", year.to_journal_name())
+            .with("first", self.journal_format.name(year.first().first()))
+            .with("last", self.journal_format.name(year.last().last()));
+        self.push_schedule_block(
+            &mut page,
+            options::Period::Year,
+            year.to_journal_name(),
+            year.first().first(),
+        );
+        self.push_seed_todos(&mut page, options::Period::Year, &context)?;
+
+        Ok(page)
+    }
+
+    /// Whether the pages link to their enclosing period with `parent::`
+    fn parent_links(&self) -> bool {
+        self.hierarchy_style == options::HierarchyStyle::Parent
+    }
+
+    fn quarters(&self) -> bool {
+        self.year_children == options::YearChildren::Quarters
+    }
+
+    fn print_quarter(&self, quarter: Quarter) -> Result<bool> {
+        if !self.is_selected(&quarter.to_journal_name()) {
+            return Ok(false);
+        }
+
+        self.update_overview(self.page_path(quarter.to_journal_path_name()), |page| {
+            self.quarter_page(quarter, page)
+        })
+    }
+
+    fn quarter_page(&self, quarter: Quarter, mut page: Page) -> Result<Page> {
+        page.push_metadata(Filters::default().push(quarter.to_journal_name(), false));
+
+        if self.parent_links() {
+            page.push_metadata(quarter.year().to_link().to_metadata("parent"));
+        }
+        if self.quarter_options.nav {
+            page.push_metadata(quarter.next().to_link().to_metadata("next"));
+            page.push_metadata(quarter.prev().to_link().to_metadata("prev"));
+        }
+
+        for month in quarter
+            .iter()
+            .map(|month| month.name_style(self.name_style))
+        {
+            page.push_content(month.to_link());
+        }
+
+        let context = TemplateContext::default()
+            .with("period", quarter.to_journal_name())
+            .with("prev", quarter.prev().to_journal_name())
+            .with("next", quarter.next().to_journal_name())
+            .with("year", quarter.year().to_journal_name())
+            .with("first", self.journal_format.name(quarter.first().first()))
+            .with("last", self.journal_format.name(quarter.last().last()));
+        self.push_schedule_block(
+            &mut page,
+            options::Period::Quarter,
+            quarter.to_journal_name(),
+            quarter.first().first(),
+        );
+        self.push_seed_todos(&mut page, options::Period::Quarter, &context)?;
+
+        Ok(page)
+    }
+
+    fn print_month(&self, month: Month) -> Result<bool> {
+        if !self.is_selected(&month.to_journal_name()) || !self.has_days(month.iter()) {
+            return Ok(false);
+        }
+
+        self.update_overview(self.page_path(month.to_journal_path_name()), |page| {
+            self.month_page(month, page)
+        })
+    }
+
+    /// Name of the --month-notes page of `month`, e.g. `2024/September Notes`
+    fn month_notes_name(month: Month) -> String {
+        format!("{} Notes", month.to_journal_name())
+    }
+
+    fn print_month_notes(&self, month: Month) -> Result<bool> {
+        if !self.is_selected(&month.to_journal_name()) || !self.has_days(month.iter()) {
+            return Ok(false);
+        }
+        let Some(template) = &self.month_notes else {
+            return Ok(false);
+        };
 
+        let name = Self::month_notes_name(month).replacen('/', "___", 1);
+        let path = self.page_path(format!("{name}.md"));
+        // only when creating the page, not to add it again
+        let template = (!path.exists()).then_some(template);
+        self.update_page(path, |mut page| {
+            page.push_metadata(month.to_link().to_metadata("month"));
+            if let Some(template) = template {
+                page.push_template(&template.render(&self.overview_context(month, &month)))?;
+            }
             Ok(page)
         })
     }
 
-    fn print_week(&self, week: IsoWeek) -> Result<()> {
-        self.update_page(self.page_path(week.to_journal_path_name()), |mut page| {
-            page.push_metadata(Filters::default().push("week", false).push("month", false));
+    fn month_page(&self, month: Month, mut page: Page) -> Result<Page> {
+        page.push_metadata(Filters::default().push("month", false));
+
+        if self.parent_links() {
+            let parent = match self.quarters() {
+                true => Quarter::from(month).to_link(),
+                false => month.year().to_link(),
+            };
+            page.push_metadata(parent.to_metadata("parent"));
+        }
+        if self.month_options.nav {
+            page.push_metadata(month.next().to_link().to_metadata("next"));
+            page.push_metadata(month.prev().to_link().to_metadata("prev"));
+        }
+        if self.month_notes.is_some() {
+            let notes = utils::Link {
+                name: Self::month_notes_name(month),
+            };
+            page.push_metadata(notes.to_metadata("notes"));
+        }
+
+        if self.month_week_index {
+            let links = self
+                .month_weeks(month)
+                .iter()
+                .map(|week| week.to_link().to_string())
+                .collect::<Vec<_>>();
+            page.push_content(links.join(" "));
+        }
 
-            if self.week_options.month {
-                page.push_metadata(Month::from(week).to_link().to_metadata("month"));
+        match self.month_layout {
+            options::MonthLayout::Flat => {
+                for (index, date) in self.days(month.iter()).enumerate() {
+                    let day = self.overview_day(&mut page, date, index)?;
+                    page.push_content(day);
+                }
             }
-            if self.week_options.nav {
-                page.push_metadata(week.next().to_link().to_metadata("next"));
-                page.push_metadata(week.prev().to_link().to_metadata("prev"));
+            options::MonthLayout::ByWeek => {
+                let mut weeks = Vec::<(Week, Vec<String>)>::new();
+                for (index, date) in self.days(month.iter()).enumerate() {
+                    let embed = self.overview_day(&mut page, date, index)?;
+                    match weeks.last_mut() {
+                        Some((week, days)) if *week == self.week(date) => days.push(embed),
+                        _ => weeks.push((self.week(date), vec![embed])),
+                    }
+                }
+                for (week, days) in weeks {
+                    page.push_nested(week.to_link(), &days);
+                }
             }
+        }
 
-            for date in week.iter() {
-                page.push_content(date.to_link().into_embedded());
+        let context = self.overview_context(month, &month);
+        self.push_schedule_block(
+            &mut page,
+            options::Period::Month,
+            month.to_journal_name(),
+            month.first(),
+        );
+        self.push_seed_todos(&mut page, options::Period::Month, &context)?;
+        self.push_overview_footer(&mut page, context)?;
+
+        Ok(page)
+    }
+
+    /// Weeks with at least a day in `month`, including the ones it shares
+    /// with the months around
+    fn month_weeks(&self, month: Month) -> Vec<Week> {
+        let mut weeks = Vec::new();
+        for date in month.iter() {
+            let week = self.week(date);
+            if weeks.last() != Some(&week) {
+                weeks.push(week);
             }
+        }
+        weeks
+    }
 
-            Ok(page)
+    fn print_week(&self, week: Week) -> Result<bool> {
+        if !self.is_selected(&week.to_journal_name()) || !self.has_days(week.iter()) {
+            return Ok(false);
+        }
+        if self.skip_future_weeks && week.first() > self.today && !self.has_notes(week.iter())? {
+            log::debug!("Skipping future week {}", week.to_journal_name());
+            return Ok(false);
+        }
+
+        self.update_overview(self.page_path(week.to_journal_path_name()), |page| {
+            self.week_page(week, page)
         })
     }
 
-    fn print_date(&self, date: NaiveDate) -> Result<()> {
-        self.update_page(
-            self.journal_path(date.to_journal_path_name()),
-            |mut page| {
-                page.push_metadata(
-                    Filters::default()
-                        .push(date.iso_week().to_journal_name(), false)
-                        .push(Month::from(date).to_journal_name(), false),
-                );
+    fn week_page(&self, week: Week, mut page: Page) -> Result<Page> {
+        page.push_metadata(Filters::default().push("week", false).push("month", false));
 
-                if self.day_options.day {
-                    let day = match date.weekday() {
-                        Weekday::Mon => "Monday",
-                        Weekday::Tue => "Tuesday",
-                        Weekday::Wed => "Wednesday",
-                        Weekday::Thu => "Thursday",
-                        Weekday::Fri => "Friday",
-                        Weekday::Sat => "Saturday",
-                        Weekday::Sun => "Sunday",
-                    };
-
-                    page.push_metadata(day.to_metadata("day"));
-                }
+        if self.parent_links() {
+            page.push_metadata(Month::from(week).to_link().to_metadata("parent"));
+        } else if self.week_options.month {
+            page.push_metadata(Month::from(week).to_link().to_metadata("month"));
+        }
+        if self.week_options.nav {
+            page.push_metadata(week.next().to_link().to_metadata("next"));
+            page.push_metadata(week.prev().to_link().to_metadata("prev"));
+        }
 
-                if self.day_options.week {
-                    page.push_metadata(date.iso_week().to_link().to_metadata("week"));
-                }
-                if self.day_options.month {
-                    page.push_metadata(Month::from(date).to_link().to_metadata("month"));
-                }
+        let mut days = self.days(week.iter()).collect::<Vec<_>>();
+        if self.day_order == options::DayOrder::Desc {
+            days.reverse();
+        }
+        for (index, date) in days.into_iter().enumerate() {
+            if self.day_captions {
+                let day = self
+                    .day_case
+                    .apply(date_utils::weekday_name(date.weekday()));
+                let caption = format!("**{day}** {}", self.journal_format.link(date));
+                let day = self.overview_day(&mut page, date, index)?;
+                page.push_nested(caption, &[day]);
+            } else {
+                let day = self.overview_day(&mut page, date, index)?;
+                page.push_content(day);
+            }
+        }
 
-                Ok(page)
-            },
-        )
+        let context = self
+            .overview_context(Month::from(week), &week)
+            .with("week", week.to_journal_name());
+        self.push_schedule_block(
+            &mut page,
+            options::Period::Week,
+            week.to_journal_name(),
+            week.first(),
+        );
+        self.push_seed_todos(&mut page, options::Period::Week, &context)?;
+        self.push_overview_footer(&mut page, context)?;
+
+        Ok(page)
     }
 
-    fn update_page<F>(&self, path: PathBuf, f: F) -> Result<()>
-    where
-        F: FnOnce(Page) -> Result<Page>,
-    {
-        log::info!("Updating page {}", path.display());
+    fn print_sprint(&self, sprint: Sprint) -> Result<bool> {
+        if !self.is_selected(&sprint.to_journal_name()) || !self.has_days(sprint.iter()) {
+            return Ok(false);
+        }
+
+        self.update_overview(self.page_path(sprint.to_journal_path_name()), |page| {
+            self.sprint_page(sprint, page)
+        })
+    }
 
-        let mut page = f(Page::new(&path))?;
+    fn sprint_page(&self, sprint: Sprint, mut page: Page) -> Result<Page> {
+        page.push_metadata(Filters::default().push("sprint", false));
+        page.push_metadata(sprint.next().to_link().to_metadata("next"));
+        page.push_metadata(sprint.prev().to_link().to_metadata("prev"));
 
-        if path.exists() {
-            page = Page::try_from(path.as_path())? + page;
+        let mut days = self.days(sprint.iter()).collect::<Vec<_>>();
+        if self.day_order == options::DayOrder::Desc {
+            days.reverse();
+        }
+        for (index, date) in days.into_iter().enumerate() {
+            let day = self.overview_day(&mut page, date, index)?;
+            page.push_content(day);
         }
 
-        page.write()?;
+        Ok(page)
+    }
 
-        Ok(())
+    /// The `Today` page, only embedding the day page of `today`
+    fn print_today(&self, today: NaiveDate) -> Result<bool> {
+        let path = self.page_path("Today".to_owned());
+        let mut page = self.new_page(&path);
+        let day = self.embed_day(&mut page, today)?;
+        page.push_content(day);
+
+        let before = if path.exists() {
+            let before = self.pages.get(&path)?;
+            page = before.clone().overwrite(page);
+            before
+        } else {
+            Page::new(&path)
+        };
+        self.save_page(&before, page, path)
     }
 
-    fn page_path(&self, name: String) -> PathBuf {
-        self.path.join("pages").join(name)
+    fn print_date(&self, date: NaiveDate) -> Result<bool> {
+        if !self.prepares_day(date) {
+            return Ok(false);
+        }
+
+        let path = self.journal_path(date);
+        // only when creating the page, not to add them again
+        let templates = match path.exists() {
+            true => Vec::new(),
+            false => self
+                .bootstrap_block
+                .iter()
+                .filter(|_| date == self.from)
+                .chain(
+                    self.weekday_templates
+                        .iter()
+                        .filter(|(day, _)| *day == date.weekday())
+                        .map(|(_, template)| template),
+                )
+                .collect(),
+        };
+        // only once, when creating the page, not to carry them again
+        let tasks = match self.carry_tasks && !path.exists() {
+            true => self.previous_tasks(date)?,
+            false => Vec::new(),
+        };
+
+        let footer = self.day_week_footer && !path.exists();
+        let heading = self.title_heading && !path.exists();
+        let previous = match self.on_this_day.filter(|_| !path.exists()) {
+            Some(years) => self.on_this_day(date, years),
+            None => Vec::new(),
+        };
+
+        self.update_page(path, |mut page| {
+            if heading {
+                let title = match &self.title_format {
+                    Some(format) => format.format(date),
+                    None => self.journal_format.name(date),
+                };
+                page.push_content(format!("# {title}"));
+            }
+            let mut page = self.day_page(date, &templates, page)?;
+            page.push_blocks(tasks);
+            if !previous.is_empty() {
+                page.push_content(format!("On this day: {}", previous.join(" ")));
+            }
+            if footer {
+                page.push_content(self.week(date).to_link().into_embedded());
+            }
+            Ok(page)
+        })
+    }
+
+    /// The nearest day before `date` that isn't skipped, the day before
+    /// when every earlier one is
+    fn prev_day(&self, date: NaiveDate) -> NaiveDate {
+        self.skip_dates.before(date).unwrap_or_else(|| date.prev())
+    }
+
+    /// The nearest day after `date` that isn't skipped, the day after when
+    /// every later one is
+    fn next_day(&self, date: NaiveDate) -> NaiveDate {
+        self.skip_dates.after(date).unwrap_or_else(|| date.next())
+    }
+
+    /// Whether the run prepares the day page of `date`, in its range
+    fn prepares_day(&self, date: NaiveDate) -> bool {
+        self.journals
+            && !self.skip_dates.contains(date)
+            && self.is_selected(&self.journal_format.name(date))
+    }
+
+    /// Links to the same day of the `years` previous years whose page
+    /// exists, latest first. A February 29 has none most years
+    fn on_this_day(&self, date: NaiveDate, years: u32) -> Vec<String> {
+        (1..=years)
+            .filter_map(|years| date.with_year(date.year().checked_sub_unsigned(years)?))
+            .filter(|date| self.journal_path(*date).exists())
+            .map(|date| self.journal_format.link(date).to_string())
+            .collect()
+    }
+
+    /// Unfinished tasks of the day before `date`, if its page exists and
+    /// wasn't created by the run, which would carry them again to every
+    /// day it creates
+    fn previous_tasks(&self, date: NaiveDate) -> Result<Vec<String>> {
+        let path = self.journal_path(date.prev());
+        if !path.exists() {
+            log::debug!("No tasks to carry from missing {}", path.display());
+            return Ok(Vec::new());
+        }
+        if self.pages.is_created(&path) {
+            log::debug!("Tasks already carried to {}", path.display());
+            return Ok(Vec::new());
+        }
+        Ok(self.pages.get(&path)?.tasks())
     }
 
-    fn journal_path(&self, name: String) -> PathBuf {
-        self.path.join("journals").join(name)
+    /// The day page of `date`, starting with the `templates` if any
+    fn day_page(&self, date: NaiveDate, templates: &[&Template], mut page: Page) -> Result<Page> {
+        page.push_metadata(
+            Filters::default()
+                .push(self.week(date).to_journal_name(), false)
+                .push(self.month(date).to_journal_name(), false),
+        );
+
+        if let Some(format) = &self.title_format {
+            page.push_metadata(format.format(date).to_metadata("title"));
+        }
+
+        if !self.aliases.is_empty() {
+            page.push_metadata(self.aliases.iter().fold(List::default(), |list, format| {
+                list.push(format.format(date))
+            }));
+        }
+
+        if self.day_options.day {
+            let day = date_utils::weekday_name(date.weekday());
+            page.push_metadata(self.day_case.apply(day).to_metadata("day"));
+        }
+
+        if self.parent_links() {
+            page.push_metadata(self.week(date).to_link().to_metadata("parent"));
+        } else {
+            if self.day_options.week {
+                page.push_metadata(self.week(date).to_link().to_metadata("week"));
+            }
+            if self.day_options.month {
+                page.push_metadata(self.month(date).to_link().to_metadata("month"));
+            }
+        }
+        if let Some(sprint) = self.sprint(date).filter(|_| self.day_options.sprint) {
+            page.push_metadata(sprint.to_link().to_metadata("sprint"));
+        }
+
+        let events = self
+            .recurrences
+            .iter()
+            .filter(|recurrence| recurrence.rule.occurs_on(date, self.from))
+            .fold(List::default(), |list, recurrence| {
+                list.push(&recurrence.name)
+            });
+        if !events.is_empty() {
+            page.push_metadata(events.into_metadata("events"));
+        }
+
+        if self.countdown {
+            let month = date_utils::days_left(date, self.month(date).last());
+            let year = date_utils::days_left(date, Year::from(date.year()).last().last());
+            page.push_metadata(month.to_metadata("days-left-in-month"));
+            page.push_metadata(year.to_metadata("days-left-in-year"));
+        }
+
+        for template in templates {
+            page.push_template(&template.render(&self.day_context(date)))?;
+        }
+
+        Ok(page)
+    }
+
+    /// The `index`th day of the overview `page`: its embed, or a link past
+    /// --max-embeds or, with --embed-existing-only, when its page neither
+    /// exists nor gets prepared by the run
+    fn overview_day(&self, page: &mut Page, date: NaiveDate, index: usize) -> Result<String> {
+        let missing = || {
+            let prepared = (self.from..=self.to).contains(&date) && self.prepares_day(date);
+            !prepared && !self.journal_path(date).exists()
+        };
+        match self.max_embeds {
+            Some(max) if index >= max => Ok(self.journal_format.link(date).to_string()),
+            _ if self.embed_existing_only && missing() => {
+                Ok(self.journal_format.link(date).to_string())
+            }
+            _ => self.embed_day(page, date),
+        }
+    }
+
+    /// Embed of the day page, or of its first block with --embed-target
+    /// block, superseding on `page` the embed of the other target
+    fn embed_day(&self, page: &mut Page, date: NaiveDate) -> Result<String> {
+        let path = self.journal_path(date);
+        let page_embed = self.journal_format.link(date).into_embedded().to_string();
+        // with the page target, the day page is only read to supersede the
+        // embeds of its first block generated before
+        if self.embed_target == options::EmbedTarget::Page && !self.embeds_blocks(page)? {
+            return Ok(page_embed);
+        }
+
+        let id = match path.exists() {
+            true => self.pages.get(&path)?.first_block_id(),
+            false => None,
+        };
+        let Some(block_embed) = id.map(|id| EmbeddedBlock { id }.to_string()) else {
+            if self.embed_target == options::EmbedTarget::Block {
+                log::warn!(
+                    "No id:: on the first block of {}, embedding the page",
+                    path.display()
+                );
+            }
+            return Ok(page_embed);
+        };
+
+        match self.embed_target {
+            options::EmbedTarget::Block => {
+                page.supersede(page_embed, block_embed.clone());
+                Ok(block_embed)
+            }
+            options::EmbedTarget::Page => {
+                page.supersede(block_embed, page_embed.clone());
+                Ok(page_embed)
+            }
+        }
+    }
+
+    /// Whether the existing overview page of `page` holds embeds of blocks
+    /// marked as generated, by an earlier --embed-target block
+    fn embeds_blocks(&self, page: &Page) -> Result<bool> {
+        Ok(page.path().exists() && self.pages.get(page.path())?.embeds_marked_blocks())
+    }
+
+    /// Push the --schedule-blocks task of the page `name` of `kind`,
+    /// scheduled on `first` as logseq writes it, e.g. `<2024-09-01 Sun>`
+    fn push_schedule_block(
+        &self,
+        page: &mut Page,
+        kind: options::Period,
+        name: String,
+        first: NaiveDate,
+    ) {
+        if self.schedule_blocks.contains(&kind) {
+            page.push_scheduled(format!("TODO Plan {name}"), first.format("%Y-%m-%d %a"));
+        }
+    }
+
+    /// Push the --seed-todos of the pages of `kind`
+    fn push_seed_todos(
+        &self,
+        page: &mut Page,
+        kind: options::Period,
+        context: &TemplateContext,
+    ) -> Result<()> {
+        for (_, template) in self.seed_todos.iter().filter(|(k, _)| *k == kind) {
+            page.push_template(&template.render(context))?;
+        }
+        Ok(())
+    }
+
+    fn push_overview_footer(&self, page: &mut Page, context: TemplateContext) -> Result<()> {
+        let Some(template) = &self.overview_footer else {
+            return Ok(());
+        };
+        page.push_template(&template.render(&context))
+    }
+
+    /// Placeholder values of the overview page of `range`, in `month`
+    fn overview_context<R>(&self, month: Month, range: &R) -> TemplateContext
+    where
+        R: DateRange<Element = NaiveDate> + JournalName + Navigation,
+    {
+        TemplateContext::default()
+            .with("period", range.to_journal_name())
+            .with("prev", range.prev().to_journal_name())
+            .with("next", range.next().to_journal_name())
+            .with("month", month.to_journal_name())
+            .with("year", Year::from(month.first().year()).to_journal_name())
+            .with("first", self.journal_format.name(range.first()))
+            .with("last", self.journal_format.name(range.last()))
+    }
+
+    /// Placeholder values of the day page of `date`
+    fn day_context(&self, date: NaiveDate) -> TemplateContext {
+        let day = self.journal_format.name(date);
+        TemplateContext::default()
+            .with("date", day.clone())
+            .with("period", day.clone())
+            .with("prev", self.journal_format.name(self.prev_day(date)))
+            .with("next", self.journal_format.name(self.next_day(date)))
+            .with("week", self.week(date).to_journal_name())
+            .with("month", self.month(date).to_journal_name())
+            .with("year", Year::from(date.year()).to_journal_name())
+            .with("first", day.clone())
+            .with("last", day)
+            .with("weekday", date_utils::weekday_name(date.weekday()))
+    }
+
+    fn new_page(&self, path: &Path) -> Page {
+        Page::new(path)
+            .mark_generated(self.mark_generated)
+            .emptied_blocks(self.emptied_blocks)
+            .collapse_embeds(self.collapse_embeds)
+            .indent(self.indent)
+            .bullet(self.bullet)
+            .block_spacing(self.block_spacing)
+            .preserve_metadata(&self.preserve_meta)
+            .sort_lists(self.sort_lists)
+            .final_newline(self.final_newline)
+            .leading_block(Some(self.leading_block))
+    }
+
+    /// Update the overview page at `path`, unless it would be blank with
+    /// --compact. Returns whether the page was written
+    fn update_overview<F>(&self, path: PathBuf, f: F) -> Result<bool>
+    where
+        F: FnOnce(Page) -> Result<Page>,
+    {
+        let mut page = f(self.new_page(&path))?;
+        if self.compact && page.is_blank() {
+            log::debug!("Skipping blank page {}", path.display());
+            return Ok(false);
+        }
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().replacen("___", "/", 1));
+        if let Some(title) = name.and_then(|name| self.titled(&name)) {
+            page.push_metadata(title.to_metadata("title"));
+        }
+        self.update_page(path, |_| Ok(page))
+    }
+
+    /// Returns whether the page was written
+    fn update_page<F>(&self, path: PathBuf, f: F) -> Result<bool>
+    where
+        F: FnOnce(Page) -> Result<Page>,
+    {
+        let mut page = f(self.new_page(&path))?;
+        if self.title_separator.is_some() {
+            page.rename_links(|name| self.titled(name));
+        }
+
+        if self.links_only {
+            if !path.exists() {
+                return Ok(false);
+            }
+            let before = self.pages.get(&path)?;
+            let page = before
+                .clone()
+                .relink(page, |name| self.is_generated_name(name));
+            return self.save_page(&before, page, path);
+        }
+
+        if let Some(created) = self.created.as_ref().filter(|_| !path.exists()) {
+            page.push_metadata(created.to_metadata("created"));
+        }
+        let stamp = self
+            .prepared_by
+            .as_ref()
+            .map(|version| version.to_metadata("prepared-by"));
+
+        let before = if path.exists() {
+            let before = self.pages.get(&path)?;
+            page = if self.append_only {
+                before.clone().append(page, self.freeze_metadata)
+            } else {
+                before.clone() + page
+            };
+            if let Some(stamp) = stamp.filter(|_| self.restamp) {
+                page.set_metadata(stamp);
+            }
+            before
+        } else {
+            if let Some(stamp) = stamp {
+                page.push_metadata(stamp);
+            }
+            Page::new(&path)
+        };
+        self.save_page(&before, page, path)
+    }
+
+    /// Write `page` over `before` if it changed. Returns whether it was
+    /// written
+    fn save_page(&self, before: &Page, mut page: Page, path: PathBuf) -> Result<bool> {
+        if !page.is_modified() {
+            log::debug!("Page {} is up to date", path.display());
+            if self.writes()
+                && self
+                    .sidecar_path(&path)
+                    .is_some_and(|sidecar| !sidecar.exists())
+            {
+                self.write_sidecar(before, &path)?;
+            }
+            return Ok(false);
+        }
+
+        if self.no_clobber && path.exists() {
+            if !self.keep_going {
+                return Err(PrepareError::PageExists { path }.into());
+            }
+            log::warn!("Skipping existing page {}", path.display());
+            return Ok(false);
+        }
+
+        log::info!("Updating page {}", path.display());
+        if log::log_enabled!(log::Level::Debug) {
+            log::debug!(
+                "Page {} links to {}",
+                path.display(),
+                page.links()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        self.write_page(before, &mut page, &path)?;
+
+        Ok(true)
+    }
+
+    fn write_page(&self, before: &Page, page: &mut Page, path: &Path) -> Result<()> {
+        if self.check {
+            println!("Out of date: {}", path.display());
+        } else if self.dry_run {
+            println!("Would update {}", path.display());
+        } else {
+            // the directory of a --journal-partition may not exist yet
+            if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+                std::fs::create_dir_all(dir).map_err(|source| PrepareError::Io {
+                    path: dir.to_path_buf(),
+                    source,
+                })?;
+            }
+            let created = !path.exists();
+            page.write_retrying(self.io_retries)?;
+            if created {
+                self.pages.created(path);
+            }
+            self.pages.written(page, path)?;
+            // the page as read back, holding the blocks kept by --append-only
+            return self.write_sidecar(&self.pages.get(path)?, path);
+        }
+
+        if self.diff {
+            print!("{}", before.diff(page));
+        }
+        Ok(())
+    }
+
+    /// Path of the --sidecar of the page at `path`
+    fn sidecar_path(&self, path: &Path) -> Option<PathBuf> {
+        self.sidecar
+            .map(|options::Sidecar::Json| path.with_extension("json"))
+    }
+
+    /// Write the --sidecar of `page`, as read from its file
+    fn write_sidecar(&self, page: &Page, path: &Path) -> Result<()> {
+        let Some(path) = self.sidecar_path(path) else {
+            return Ok(());
+        };
+        retry::retry(self.io_retries, || {
+            std::fs::write(&path, page.sidecar().to_string())
+        })
+        .map_err(|source| PrepareError::Io { path, source })?;
+        Ok(())
+    }
+
+    /// The days of `dates` that are not skipped
+    fn days<'a, I>(&'a self, dates: I) -> impl Iterator<Item = NaiveDate> + 'a
+    where
+        I: Iterator<Item = NaiveDate> + 'a,
+    {
+        dates.filter(|date| !self.skip_dates.contains(*date))
+    }
+
+    /// Whether one of the day pages of `dates` has something written by
+    /// hand
+    fn has_notes<I: Iterator<Item = NaiveDate>>(&self, dates: I) -> Result<bool> {
+        for date in dates {
+            let path = self.journal_path(date);
+            if path.exists()
+                && !self.is_generated_page(
+                    &self.pages.get(&path)?,
+                    &self.journal_format.name(date),
+                    Some(date),
+                )
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Whether a page is due for a period of `dates`, some not being skipped
+    fn has_days<I: Iterator<Item = NaiveDate>>(&self, dates: I) -> bool {
+        self.days(dates).next().is_some()
+    }
+
+    /// Path of the page `name`, the name of its markdown file
+    fn page_path(&self, name: String) -> PathBuf {
+        let dir = if self.flat {
+            self.path.clone()
+        } else {
+            self.path.join("pages")
+        };
+        dir.join(name).with_extension(&self.page_extension)
+    }
+
+    /// Path of the day page of `date`, in its --journal-partition if any,
+    /// unless the page already exists outside of it
+    fn journal_path(&self, date: NaiveDate) -> PathBuf {
+        let dir = if self.flat {
+            self.path.clone()
+        } else {
+            self.path.join("journals")
+        };
+        let file =
+            PathBuf::from(date.to_journal_path_name()).with_extension(&self.journal_extension);
+        let Some(partition) = self.journal_partition else {
+            return dir.join(file);
+        };
+
+        let path = dir.join(partition.dir(date)).join(&file);
+        let unpartitioned = dir.join(file);
+        if !path.exists() && unpartitioned.exists() {
+            log::debug!(
+                "{} is outside of its partition, updating it there",
+                unpartitioned.display()
+            );
+            return unpartitioned;
+        }
+        path
+    }
+}
+
+/// Whether `path` holds day or period pages directly, files with one of
+/// `extensions`, the layout of flat graphs. Other files like a README.md
+/// don't make a graph
+fn has_pages(path: &Path, extensions: &[&str]) -> Result<bool> {
+    if !path.is_dir() {
+        return Ok(false);
+    }
+    for entry in std::fs::read_dir(path)? {
+        let name = entry?.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if extensions
+            .iter()
+            .filter_map(|extension| markdown_name(name, extension))
+            .any(|name| is_journal_name(&name))
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the markdown file `name` is the one of a day or period page, in
+/// any style
+fn is_journal_name(name: &str) -> bool {
+    NaiveDate::from_journal_path_name(name).is_some()
+        || name
+            .strip_suffix(".md")
+            .and_then(|name| utils::week_year_number(&name.replacen("___", "/", 1)))
+            .is_some()
+        || Month::from_journal_path_name(name).is_some()
+        || Quarter::from_journal_path_name(name).is_some()
+        || Year::from_journal_path_name(name).is_some()
+}
+
+/// Name of the markdown file of the page in the file `name`, if it has
+/// `extension`
+fn markdown_name(name: &str, extension: &str) -> Option<String> {
+    let stem = name.strip_suffix(extension)?.strip_suffix('.')?;
+    Some(format!("{stem}.md"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+    use indoc::indoc;
+
+    /// An empty graph, with its journals and pages directories
+    pub(crate) fn graph() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("journals").create_dir_all()?;
+        temp_dir.child("pages").create_dir_all()?;
+        Ok(temp_dir)
+    }
+
+    pub(crate) fn preparer(temp_dir: &TempDir, args: &[&str]) -> Result<Preparer> {
+        let path = temp_dir.path().to_str().unwrap();
+        let mut cli = options::Cli::parse_with_config(
+            ["logseq-journal-prepare", "--path", path]
+                .iter()
+                .chain(args),
+        )?;
+        // Don't depend on the locale running the tests
+        cli.week_start.get_or_insert(WeekStart::Monday);
+        Preparer::try_from(cli)
+    }
+
+    #[test]
+    fn status() -> Result<()> {
+        let temp_dir = graph()?;
+        let status = |args: &[&str]| -> Result<Status> {
+            let preparer = preparer(&temp_dir, args)?;
+            Ok(Status::of(&preparer, preparer.run()))
+        };
+        let args = ["--from", "2024-09-02", "--to", "2024-09-02"];
+
+        assert_eq!(Status::Stale, status(&[&args[..], &["--check"]].concat())?);
+        assert_eq!(Status::Success, status(&args)?);
+        assert_eq!(Status::Unchanged, status(&args)?);
+        assert_eq!(
+            Status::Success,
+            status(&[&args[..], &["--check"]].concat())?
+        );
+
+        temp_dir
+            .child("journals/2024_09_03.md")
+            .write_str("- notes\n")?;
+        let args = ["--from", "2024-09-03", "--to", "2024-09-03"];
+        assert_eq!(
+            Status::Clobber,
+            status(&[&args[..], &["--no-clobber"]].concat())?
+        );
+
+        let mut pruner = preparer(&temp_dir, &["--from", "2024-10-01", "--prune"])?;
+        pruner.confirm = Confirm::Refuse;
+        assert_eq!(Status::Unconfirmed, Status::of(&pruner, pruner.run()));
+
+        temp_dir
+            .child("journals/2024_09_04.md")
+            .write_str("filters:: {\"2024/September\" maybe}\n\n-\n")?;
+        let args = ["--from", "2024-09-04", "--to", "2024-09-04"];
+        assert_eq!(Status::Invalid, status(&args)?);
+        // the other failures aren't invalid pages
+        let preparer = preparer(&temp_dir, &args)?;
+        let error = anyhow::anyhow!("unexpected");
+        assert_eq!(Status::Failure, Status::of(&preparer, Err(error)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn week_start() -> Result<()> {
+        for (week_start, from, to) in [
+            ("sunday", "2024-09-01", "2024-09-07"),
+            ("monday", "2024-09-02", "2024-09-08"),
+        ] {
+            let temp_dir = graph()?;
+            let args = [
+                "--week-start",
+                week_start,
+                "--from",
+                "2024-08-31",
+                "--to",
+                "2024-09-09",
+            ];
+            preparer(&temp_dir, &args)?.run()?;
+
+            let from = from.parse::<NaiveDate>()?;
+            let to = to.parse::<NaiveDate>()?;
+            for date in from.iter_days().take_while(|date| *date <= to) {
+                let page = std::fs::read_to_string(
+                    temp_dir
+                        .child("journals")
+                        .child(date.to_journal_path_name()),
+                )?;
+                assert!(page.contains("week:: [[2024/Week 36]]"), "{date}: {page}");
+            }
+            for date in [from.prev(), to.next()] {
+                let page = std::fs::read_to_string(
+                    temp_dir
+                        .child("journals")
+                        .child(date.to_journal_path_name()),
+                )?;
+                assert!(!page.contains("week:: [[2024/Week 36]]"), "{date}: {page}");
+            }
+
+            let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 36.md"))?;
+            assert!(
+                week.contains(&format!("-\n- {{{{embed [[{from}]]}}}}\n")),
+                "{week}"
+            );
+            assert!(
+                week.ends_with(&format!("- {{{{embed [[{to}]]}}}}\n")),
+                "{week}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bootstrap_block() -> Result<()> {
+        let temp_dir = graph()?;
+        let template = temp_dir.child("bootstrap.md");
+        template.write_str("- Start at [[Index]]\n- Week [[{week}]] of [[{month}]]\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--bootstrap-block",
+            template.path().to_str().unwrap(),
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let first = temp_dir.child("journals/2024_09_01.md");
+        let content = std::fs::read_to_string(first.path())?;
+        assert!(content.ends_with(indoc! {"
+            -
+            - Start at [[Index]]
+            - Week [[2024/Week 35]] of [[2024/September]]
+        "}));
+        let second = std::fs::read_to_string(temp_dir.child("journals/2024_09_02.md"))?;
+        assert!(!second.contains("Index"), "{second}");
+
+        // Not added back to an existing page
+        first.write_str(&content.replace("- Start at [[Index]]\n", ""))?;
+        preparer(&temp_dir, &args)?.run()?;
+        let content = std::fs::read_to_string(first.path())?;
+        assert!(!content.contains("Index"), "{content}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn seed_todos() -> Result<()> {
+        let temp_dir = graph()?;
+        let review = temp_dir.child("review.md");
+        review.write_str("- TODO Review [[{period}]]\n- TODO Plan [[{next}]]\n")?;
+
+        let month = format!("month={}", review.path().display());
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-01",
+            "--mark-generated",
+            "--seed-todos",
+            &month,
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let page = temp_dir.child("pages/2024___September.md");
+        let text = std::fs::read_to_string(page.path())?;
+        assert!(
+            text.ends_with(indoc! {"
+                - TODO Review [[2024/September]]
+                  generated:: true
+                - TODO Plan [[2024/October]]
+                  generated:: true
+            "}),
+            "{text}"
+        );
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 35.md"))?;
+        assert!(!week.contains("TODO"), "{week}");
+
+        // recognized once done, even with notes under it
+        page.write_str(&text.replace(
+            "- TODO Review [[2024/September]]\n  generated:: true\n",
+            "- DONE Review [[2024/September]]\n  generated:: true\n\t- went well\n",
+        ))?;
+        preparer(&temp_dir, &args)?.run()?;
+        let text = std::fs::read_to_string(page.path())?;
+        assert_eq!(
+            1,
+            text.matches("Review [[2024/September]]").count(),
+            "{text}"
+        );
+        assert!(text.contains("- DONE Review"), "{text}");
+        assert_eq!(1, text.matches("Plan [[2024/October]]").count(), "{text}");
+
+        assert!(preparer(&temp_dir, &["--seed-todos", &month]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn month_notes() -> Result<()> {
+        let temp_dir = graph()?;
+        let reflections = temp_dir.child("reflections.md");
+        reflections.write_str("- ## Reflections on {month}\n- What went well?\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--month-notes",
+            reflections.path().to_str().unwrap(),
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let notes = temp_dir.child("pages/2024___September Notes.md");
+        notes.assert(indoc! {"
+            month:: [[2024/September]]
+
+            -
+            - ## Reflections on 2024/September
+            - What went well?
+        "});
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___September.md"))?;
+        assert!(
+            month.contains("\nnotes:: [[2024/September Notes]]\n"),
+            "{month}"
+        );
+        assert!(month.contains("{{embed [[2024-09-01]]}}"), "{month}");
+
+        // the template isn't added back to the page written in
+        let written = "month:: [[2024/September]]\n\n-\n- A good month\n";
+        notes.write_str(written)?;
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+        notes.assert(written);
+
+        Ok(())
+    }
+
+    #[test]
+    fn schedule_blocks() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-01"];
+        let scheduled = [&args[..], &["--schedule-blocks", "month,year"]].concat();
+        // without the marker, a task marked DONE would be added again
+        assert!(preparer(&temp_dir, &scheduled).is_err());
+
+        // the marker comes after the schedule
+        let scheduled = [&scheduled[..], &["--mark-generated"]].concat();
+        preparer(&temp_dir, &scheduled)?.run()?;
+        let page = temp_dir.child("pages/2024___September.md");
+        let text = std::fs::read_to_string(page.path())?;
+        let task =
+            "- TODO Plan 2024/September\n  SCHEDULED: <2024-09-01 Sun>\n  generated:: true\n";
+        assert!(text.ends_with(task), "{text}");
+        let year = std::fs::read_to_string(temp_dir.child("pages/2024.md"))?;
+        assert!(
+            year.contains("- TODO Plan 2024\n  SCHEDULED: <2024-01-01 Mon>\n"),
+            "{year}"
+        );
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 35.md"))?;
+        assert!(!week.contains("SCHEDULED"), "{week}");
+        assert_eq!(0, preparer(&temp_dir, &scheduled)?.run()?.updated);
+
+        // and the task isn't added again once done
+        page.write_str(&text.replace("TODO", "DONE"))?;
+        preparer(&temp_dir, &scheduled)?.run()?;
+        let text = std::fs::read_to_string(page.path())?;
+        assert_eq!(1, text.matches("Plan 2024/September").count(), "{text}");
+
+        // indented like the page
+        let tabs = [&scheduled[..], &["--indent", "tab"]].concat();
+        let quarter = temp_dir.child("pages/2024___Q3.md");
+        let quarters = [
+            &tabs[..],
+            &[
+                "--schedule-blocks",
+                "quarter",
+                "--year-children",
+                "quarters",
+            ],
+        ]
+        .concat();
+        preparer(&temp_dir, &quarters)?.run()?;
+        let text = std::fs::read_to_string(quarter.path())?;
+        assert!(
+            text.contains("- TODO Plan 2024/Q3\n\tSCHEDULED: <2024-07-01 Mon>\n"),
+            "{text}"
+        );
+        let quarters = [
+            &scheduled[..],
+            &[
+                "--schedule-blocks",
+                "quarter",
+                "--year-children",
+                "quarters",
+            ],
+        ]
+        .concat();
+        preparer(&temp_dir, &quarters)?.run()?;
+        let updated = std::fs::read_to_string(quarter.path())?;
+        assert_eq!(1, updated.matches("Plan 2024/Q3").count(), "{updated}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn weekday_template() -> Result<()> {
+        let temp_dir = graph()?;
+        let plan = temp_dir.child("plan.md");
+        plan.write_str("- Plan [[{week}]]\n")?;
+        let review = temp_dir.child("review.md");
+        review.write_str("- Review [[{week}]]\n")?;
+
+        let mon = format!("mon={}", plan.path().display());
+        let fri = format!("friday={}", review.path().display());
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-06",
+            "--weekday-template",
+            &mon,
+            "--weekday-template",
+            &fri,
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let day =
+            |day| std::fs::read_to_string(temp_dir.child(format!("journals/2024_09_0{day}.md")));
+        let monday = day(2)?;
+        assert!(monday.ends_with("-\n- Plan [[2024/Week 36]]\n"), "{monday}");
+        assert!(!monday.contains("Review"), "{monday}");
+        let friday = day(6)?;
+        assert!(
+            friday.ends_with("-\n- Review [[2024/Week 36]]\n"),
+            "{friday}"
+        );
+        let tuesday = day(3)?;
+        assert!(
+            !tuesday.contains("Plan") && !tuesday.contains("Review"),
+            "{tuesday}"
+        );
+
+        // Not added back to an existing page
+        let monday_path = temp_dir.child("journals/2024_09_02.md");
+        monday_path.write_str(&monday.replace("- Plan [[2024/Week 36]]\n", ""))?;
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!day(2)?.contains("Plan"));
+
+        assert!(preparer(&temp_dir, &["--weekday-template", "someday=plan.md"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn overview_footer() -> Result<()> {
+        let temp_dir = graph()?;
+        let template = temp_dir.child("footer.md");
+        template.write_str("- {period} of {year}, after [[{prev}]], until [[{last}]]\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "--overview-footer",
+            template.path().to_str().unwrap(),
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 36.md"))?;
+        assert!(week
+            .ends_with("- 2024/Week 36 of 2024, after [[2024/Week 35]], until [[2024-09-08]]\n"));
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___September.md"))?;
+        assert!(month
+            .ends_with("- 2024/September of 2024, after [[2024/August]], until [[2024-09-30]]\n"));
+
+        template.write_str("- {period} {today}\n")?;
+        let error = preparer(&temp_dir, &args).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::FormatInvalid { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn name_style() -> Result<()> {
+        for (style, week, month, next_month, july) in [
+            (
+                "name",
+                "2024/Week 35",
+                "2024/September",
+                "2024/October",
+                "2024/July",
+            ),
+            ("numeric", "2024/W35", "2024/09", "2024/10", "2024/07"),
+        ] {
+            let temp_dir = graph()?;
+            let args = [
+                "--from",
+                "2024-09-01",
+                "--to",
+                "2024-09-01",
+                "--name-style",
+                style,
+                "--year-children",
+                "quarters",
+            ];
+            preparer(&temp_dir, &args)?.run()?;
+
+            let day = std::fs::read_to_string(temp_dir.child("journals/2024_09_01.md"))?;
+            assert!(day.contains(&format!("week:: [[{week}]]")), "{day}");
+            assert!(day.contains(&format!("\"{month}\" false")), "{day}");
+
+            let path = |name: &str| format!("pages/{}.md", name.replacen('/', "___", 1));
+            let week = std::fs::read_to_string(temp_dir.child(path(week)))?;
+            assert!(week.contains("\n- {{embed [[2024-09-01]]}}"), "{week}");
+            let month = std::fs::read_to_string(temp_dir.child(path(month)))?;
+            assert!(
+                month.contains(&format!("next:: [[{next_month}]]")),
+                "{month}"
+            );
+            let quarter = std::fs::read_to_string(temp_dir.child("pages/2024___Q3.md"))?;
+            assert!(quarter.contains(&format!("[[{july}]]")), "{quarter}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn links() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("journals").create_dir_all()?;
+        let args = ["--from", "2024-09-02", "--to", "2024-09-02"];
+        let preparer = preparer(&temp_dir, &args)?;
+        let date = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        // the links of the pages as prepared, regardless of the existing
+        // ones and without writing them
+        let page = || preparer.new_page(Path::new(""));
+        let names = |page: Page| {
+            page.links()
+                .into_iter()
+                .map(|link| link.name)
+                .collect::<Vec<_>>()
+        };
+        let days = |first: u32, last: u32| {
+            (first..=last)
+                .map(|day| format!("2024-09-{day:02}"))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec!["2024/Week 36"],
+            names(preparer.day_page(date, &[], page())?)
+        );
+        assert_eq!(
+            [
+                vec![
+                    "2024/September".to_owned(),
+                    "2024/Week 37".to_owned(),
+                    "2024/Week 35".to_owned()
+                ],
+                days(2, 8)
+            ]
+            .concat(),
+            names(preparer.week_page(preparer.week(date), page())?)
+        );
+        assert_eq!(
+            [
+                vec!["2024/October".to_owned(), "2024/August".to_owned()],
+                days(1, 30)
+            ]
+            .concat(),
+            names(preparer.month_page(preparer.month(date), page())?)
+        );
+        let year = Year::from(2024);
+        assert_eq!(
+            vec![
+                "2025",
+                "2023",
+                "2024/January",
+                "2024/February",
+                "2024/March",
+                "2024/April",
+                "2024/May",
+                "2024/June",
+                "2024/July",
+                "2024/August",
+                "2024/September",
+                "2024/October",
+                "2024/November",
+                "2024/December",
+            ],
+            names(preparer.year_page(year, page())?)
+        );
+        // nothing is written
+        assert!(!temp_dir.child("pages").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn year_children() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = ["--from", "2024-09-29", "--to", "2024-10-02"];
+        preparer(
+            &temp_dir,
+            &[&args[..], &["--year-children", "quarters"]].concat(),
+        )?
+        .run()?;
+
+        temp_dir.child("pages/2024.md").assert(indoc! {r#"
+            filters:: {"2024" false}
+            next:: [[2025]]
+            prev:: [[2023]]
+
+            -
+            - [[2024/Q1]]
+            - [[2024/Q2]]
+            - [[2024/Q3]]
+            - [[2024/Q4]]
+        "#});
+        temp_dir.child("pages/2024___Q3.md").assert(indoc! {r#"
+            filters:: {"2024/Q3" false}
+            next:: [[2024/Q4]]
+            prev:: [[2024/Q2]]
+
+            -
+            - [[2024/July]]
+            - [[2024/August]]
+            - [[2024/September]]
+        "#});
+        assert!(temp_dir.child("pages/2024___Q4.md").exists());
+        assert!(temp_dir.child("pages/2024___September.md").exists());
+        assert!(temp_dir.child("pages/2024___October.md").exists());
+
+        let temp_dir = graph()?;
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("pages/2024___Q3.md").exists());
+        let year = std::fs::read_to_string(temp_dir.child("pages/2024.md"))?;
+        assert!(year.ends_with("- [[2024/November]]\n- [[2024/December]]\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = ["--from", "2024-09-29", "--to", "2024-10-02"];
+        let summary = preparer(&temp_dir, &[&args[..], &["--check"]].concat())?.run()?;
+        assert_eq!(summary.updated, 9);
+        assert!(!temp_dir.child("journals/2024_09_29.md").exists());
+
+        preparer(&temp_dir, &args)?.run()?;
+        let summary = preparer(&temp_dir, &[&args[..], &["--check"]].concat())?.run()?;
+        assert_eq!(summary.updated, 0);
+
+        temp_dir
+            .child("journals/2024_10_01.md")
+            .write_str("- hello\n")?;
+        let summary = preparer(&temp_dir, &[&args[..], &["--check"]].concat())?.run()?;
+        assert_eq!(summary.updated, 1);
+        temp_dir.child("journals/2024_10_01.md").assert("- hello\n");
+
+        assert!(preparer(&temp_dir, &["--check", "--dry-run"]).is_err());
+        assert!(preparer(&temp_dir, &["--check", "--prune"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn iso_week_title() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let format = "%Y-%m-%d (W{isoweek})";
+        for (from, to) in [("2024-09-01", "2024-09-02"), ("2024-12-29", "2024-12-30")] {
+            let args = [
+                "--from",
+                from,
+                "--to",
+                to,
+                "--title-format",
+                format,
+                "--aliases",
+                "{isoyear}-W{isoweek}-%u",
+            ];
+            preparer(&temp_dir, &args)?.run()?;
+        }
+
+        for (name, title, alias) in [
+            ("2024_09_01", "2024-09-01 (W35)", "2024-W35-7"),
+            ("2024_09_02", "2024-09-02 (W36)", "2024-W36-1"),
+            ("2024_12_29", "2024-12-29 (W52)", "2024-W52-7"),
+            // the ISO week-year is already 2025
+            ("2024_12_30", "2024-12-30 (W01)", "2025-W01-1"),
+        ] {
+            let page = std::fs::read_to_string(temp_dir.child(format!("journals/{name}.md")))?;
+            assert!(page.contains(&format!("title:: {title}\n")), "{page}");
+            assert!(page.contains(&format!("alias:: [[{alias}]]\n")), "{page}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn aliases() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_01.md")
+            .write_str("alias:: [[Labor day weekend]]\n\n- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--aliases",
+            "%b %-d, %Y",
+            "--aliases",
+            "%d/%m/%Y",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        temp_dir.child("journals/2024_09_01.md").assert(indoc! {r#"
+            alias:: [[Labor day weekend]], [[Sep 1, 2024]], [[01/09/2024]]
+            filters:: {"2024/September" false, "2024/Week 35" false}
+            day:: Sunday
+            week:: [[2024/Week 35]]
+
+            -
+            - hello
+        "#});
+
+        let summary = preparer(&temp_dir, &args)?.run()?;
+        assert_eq!(summary.updated, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn day_order() -> Result<()> {
+        for (week_start, day_order, first, last) in [
+            ("sunday", "asc", "2024-09-01", "2024-09-07"),
+            ("sunday", "desc", "2024-09-07", "2024-09-01"),
+            ("monday", "asc", "2024-09-02", "2024-09-08"),
+            ("monday", "desc", "2024-09-08", "2024-09-02"),
+        ] {
+            let temp_dir = graph()?;
+            let args = [
+                "--week-start",
+                week_start,
+                "--day-order",
+                day_order,
+                "--from",
+                "2024-09-03",
+                "--to",
+                "2024-09-03",
+            ];
+            preparer(&temp_dir, &args)?.run()?;
+
+            let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 36.md"))?;
+            let embeds = week
+                .lines()
+                .filter_map(|line| line.strip_prefix("- {{embed [["))
+                .collect::<Vec<_>>();
+            assert_eq!(7, embeds.len(), "{week}");
+            assert!(
+                embeds[0].starts_with(first),
+                "{week_start} {day_order}: {week}"
+            );
+            assert!(
+                embeds[6].starts_with(last),
+                "{week_start} {day_order}: {week}"
+            );
+            assert!(week.contains("month:: [[2024/September]]"));
+            assert!(week.contains("next:: [[2024/Week 37]]"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_dates() -> Result<()> {
+        let temp_dir = graph()?;
+        let skip = temp_dir.child("skip");
+        skip.write_str("2024-09-03\n2024-09-05..2024-09-06\n2024-09-09..2024-09-15\n")?;
+        let template = temp_dir.child("wednesday.md");
+        template.write_str("- After [[{prev}]], before [[{next}]]\n")?;
+        let wednesday = format!("wed={}", template.path().display());
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-15",
+            "--skip-dates-file",
+            skip.path().to_str().unwrap(),
+            "--weekday-template",
+            &wednesday,
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        for (day, exists) in [(2, true), (3, false), (4, true), (5, false), (6, false)] {
+            let path = format!("journals/2024_09_{day:02}.md");
+            assert_eq!(exists, temp_dir.child(path).exists(), "2024-09-{day:02}");
+        }
+        // the links step over the skipped days
+        let day = std::fs::read_to_string(temp_dir.child("journals/2024_09_04.md"))?;
+        assert!(
+            day.ends_with("- After [[2024-09-02]], before [[2024-09-07]]\n"),
+            "{day}"
+        );
+
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 36.md"))?;
+        assert!(week.contains("- {{embed [[2024-09-02]]}}\n"));
+        assert!(!week.contains("[[2024-09-03]]"));
+        assert!(week.contains("- {{embed [[2024-09-04]]}}\n"));
+        assert!(!week.contains("[[2024-09-05]]"));
+        assert!(week.contains("- {{embed [[2024-09-08]]}}\n"));
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___September.md"))?;
+        assert!(!month.contains("[[2024-09-03]]"));
+        assert!(month.contains("[[2024-09-30]]"));
+
+        // every day of the week is skipped
+        assert!(!temp_dir.child("pages/2024___Week 37.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_clobber() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02", "--no-clobber"];
+
+        // the pages it created are up to date on the next run
+        preparer(&temp_dir, &args)?.run()?;
+        assert_eq!(preparer(&temp_dir, &args)?.run()?.updated, 0);
+
+        let existing = temp_dir.child("journals/2024_09_03.md");
+        existing.write_str("- hello\n")?;
+        let args = ["--from", "2024-09-03", "--to", "2024-09-04", "--no-clobber"];
+        let error = preparer(&temp_dir, &args)?.run().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::PageExists { .. })
+        ));
+        existing.assert("- hello\n");
+
+        preparer(&temp_dir, &[&args[..], &["--keep-going"]].concat())?.run()?;
+        existing.assert("- hello\n");
+        assert!(temp_dir.child("journals/2024_09_04.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sprints() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = [
+            "--from",
+            "2024-01-13",
+            "--to",
+            "2024-01-16",
+            "--sprint-length",
+            "14",
+            "--sprint-anchor",
+            "2024-01-01",
+            "--day",
+            "sprint",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let first = std::fs::read_to_string(temp_dir.child("pages/Sprint 1.md"))?;
+        assert!(
+            first.starts_with(indoc! {"
+            filters:: {\"sprint\" false}
+            next:: [[Sprint 2]]
+            prev:: [[Sprint 0]]
+
+            -
+            - {{embed [[2024-01-01]]}}
+        "}),
+            "{first}"
+        );
+        assert!(first.ends_with("- {{embed [[2024-01-14]]}}\n"), "{first}");
+        let second = std::fs::read_to_string(temp_dir.child("pages/Sprint 2.md"))?;
+        assert!(second.contains("- {{embed [[2024-01-15]]}}\n"), "{second}");
+        assert!(!temp_dir.child("pages/Sprint 3.md").exists());
+
+        let day = std::fs::read_to_string(temp_dir.child("journals/2024_01_15.md"))?;
+        assert!(day.contains("sprint:: [[Sprint 2]]\n"), "{day}");
+        let day = std::fs::read_to_string(temp_dir.child("journals/2024_01_14.md"))?;
+        assert!(day.contains("sprint:: [[Sprint 1]]\n"), "{day}");
+
+        let graph = preparer(&temp_dir, &[&args[..], &["--plan-graph"]].concat())?.plan_graph();
+        assert!(
+            graph.contains("\"2024-01-15\" -> \"Sprint 2\" [label=\"sprint\"];\n"),
+            "{graph}"
+        );
+        assert!(
+            graph.contains("\"Sprint 2\" -> \"Sprint 3\" [label=\"next\"];\n"),
+            "{graph}"
+        );
+
+        // the sprints before the range are pruned
+        let args = [
+            "--from",
+            "2024-02-12",
+            "--to",
+            "2024-02-25",
+            "--sprint-length",
+            "14",
+            "--sprint-anchor",
+            "2024-01-01",
+            "--prune",
+        ];
+        let plan = preparer(&temp_dir, &args)?.prune_plan()?;
+        for name in ["Sprint 1.md", "Sprint 2.md"] {
+            let path = temp_dir.child("pages").child(name).to_path_buf();
+            assert!(plan.iter().any(|(pruned, _)| *pruned == path), "{name}");
+        }
+
+        assert!(preparer(&temp_dir, &["--sprint-length", "14"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn links_only() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "--day",
+            "day,week,month",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let day = temp_dir.child("journals/2024_09_02.md");
+        let content = std::fs::read_to_string(&day)? + "- notes\n";
+        day.write_str(&content)?;
+        let week = temp_dir.child("pages/2024___Week 36.md");
+        let content = std::fs::read_to_string(&week)?.replace("- {{embed [[2024-09-08]]}}\n", "");
+        week.write_str(&content)?;
+        // a link written by hand under a key of the tool
+        let year = temp_dir.child("pages/2024.md");
+        let content = std::fs::read_to_string(&year)?.replace(
+            "prev:: [[2023]]\n",
+            "prev:: [[2023]]\nparent:: [[Project]]\n",
+        );
+        year.write_str(&content)?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-03",
+            "--day",
+            "day,week,month",
+            "--hierarchy-style",
+            "parent",
+            "--links-only",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let content = std::fs::read_to_string(&day)?;
+        assert!(content.contains("parent:: [[2024/Week 36]]\n"), "{content}");
+        assert!(
+            !content.contains("week::") && !content.contains("month::"),
+            "{content}"
+        );
+        assert!(content.contains("day:: Monday\n"), "{content}");
+        assert!(content.ends_with("-\n- notes\n"), "{content}");
+
+        // the embeds stay as they are, and no page gets created
+        let content = std::fs::read_to_string(&week)?;
+        assert!(
+            content.contains("parent:: [[2024/September]]\n"),
+            "{content}"
+        );
+        assert!(
+            content.contains("- {{embed [[2024-09-07]]}}\n"),
+            "{content}"
+        );
+        assert!(!content.contains("2024-09-08"), "{content}");
+        assert!(!temp_dir.child("journals/2024_09_03.md").exists());
+        let content = std::fs::read_to_string(&year)?;
+        assert!(content.contains("parent:: [[Project]]\n"), "{content}");
+
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hierarchy_style() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "--hierarchy-style",
+            "parent",
+            "--day",
+            "day,week,month",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let read = |name: &str| std::fs::read_to_string(temp_dir.child(name));
+        for (name, parent) in [
+            ("journals/2024_09_02.md", "2024/Week 36"),
+            ("pages/2024___Week 36.md", "2024/September"),
+            ("pages/2024___September.md", "2024"),
+        ] {
+            let page = read(name)?;
+            assert_eq!(1, page.matches("parent::").count(), "{page}");
+            assert!(page.contains(&format!("parent:: [[{parent}]]\n")), "{page}");
+            assert!(
+                !page.contains("week::") && !page.contains("month::"),
+                "{page}"
+            );
+            assert!(page.contains("day::") || page.contains("next::"), "{page}");
+        }
+        assert!(!read("pages/2024.md")?.contains("parent::"));
+
+        preparer(
+            &temp_dir,
+            &[&args[..], &["--year-children", "quarters"]].concat(),
+        )?
+        .run()?;
+        let month = read("pages/2024___September.md")?;
+        assert!(month.contains("parent:: [[2024/Q3]]\n"), "{month}");
+        let quarter = read("pages/2024___Q3.md")?;
+        assert!(quarter.contains("parent:: [[2024]]\n"), "{quarter}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_embeds() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = [
+            "--from",
+            "2024-08-01",
+            "--to",
+            "2024-08-01",
+            "--max-embeds",
+            "7",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___August.md"))?;
+        assert_eq!(7, month.matches("- {{embed [[").count(), "{month}");
+        assert_eq!(24, month.matches("\n- [[2024-08-").count(), "{month}");
+        assert!(month.contains("- {{embed [[2024-08-07]]}}\n- [[2024-08-08]]\n"));
+
+        // a week has fewer days than the limit
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 31.md"))?;
+        assert_eq!(7, week.matches("- {{embed [[").count(), "{week}");
+        // the year pages link to their months whatever the limit
+        let year = std::fs::read_to_string(temp_dir.child("pages/2024.md"))?;
+        assert_eq!(12, year.matches("- [[2024/").count(), "{year}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn embed_existing_only() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_05.md")
+            .write_str("- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-03",
+            "--embed-existing-only",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let week = temp_dir.child("pages/2024___Week 36.md");
+        let content = std::fs::read_to_string(&week)?;
+        let expected = indoc! {"
+            -
+            - {{embed [[2024-09-02]]}}
+            - {{embed [[2024-09-03]]}}
+            - [[2024-09-04]]
+            - {{embed [[2024-09-05]]}}
+            - [[2024-09-06]]
+            - [[2024-09-07]]
+            - [[2024-09-08]]
+        "};
+        assert!(content.ends_with(expected), "{content}");
+
+        // the links become embeds, in place, once their page exists
+        let args = [
+            "--from",
+            "2024-09-04",
+            "--to",
+            "2024-09-04",
+            "--embed-existing-only",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        let content = std::fs::read_to_string(&week)?;
+        assert!(
+            content.contains("- {{embed [[2024-09-03]]}}\n- {{embed [[2024-09-04]]}}\n- {{embed [[2024-09-05]]}}\n"),
+            "{content}"
+        );
+        assert!(!content.contains("- [[2024-09-04]]"), "{content}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn day_captions() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "--day-captions",
+            "--day-case",
+            "upper",
+            "--journal-format",
+            "%Y/%m/%d",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        preparer(&temp_dir, &args)?.run()?;
+
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 36.md"))?;
+        assert!(
+            week.contains(indoc! {"
+            - **MONDAY** [[2024/09/02]]
+              - {{embed [[2024/09/02]]}}
+            - **TUESDAY** [[2024/09/03]]
+              - {{embed [[2024/09/03]]}}
+        "}),
+            "{week}"
+        );
+        assert_eq!(1, week.matches("**MONDAY**").count(), "{week}");
+        assert_eq!(7, week.matches("{{embed").count(), "{week}");
+
+        // the captions and the weeks of the month are taken as generated
+        let by_week = [
+            &args[..],
+            &["--day", "day,week,month", "--month-layout", "by-week"],
+        ]
+        .concat();
+        preparer(&temp_dir, &by_week)?.run()?;
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___September.md"))?;
+        assert!(month.contains("- [[2024/Week 36]]\n  - {{embed"), "{month}");
+        let args = [
+            "--from",
+            "2024-11-01",
+            "--to",
+            "2024-11-01",
+            "--journal-format",
+            "%Y/%m/%d",
+            "--prune",
+            "--yes",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("pages/2024___Week 36.md").exists());
+        assert!(!temp_dir.child("pages/2024___September.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn page_cache() -> Result<()> {
+        let temp_dir = graph()?;
+        for day in 1..=3 {
+            temp_dir
+                .child(format!("journals/2024_09_0{day}.md"))
+                .write_str(&format!(
+                    "- TODO {day}\n  id:: 66e2a5c4-5ab0-4cfa-a3e4-2b1d8c6b1f1{day}\n"
+                ))?;
+        }
+
+        // each day page is read for its update, the embeds of the week and
+        // month pages and the tasks of the next day
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-03",
+            "--embed-target",
+            "block",
+            "--carry-tasks",
+        ];
+        let preparer = preparer(&temp_dir, &args)?;
+        preparer.run()?;
+        assert_eq!(3, preparer.pages.reads());
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_future_weeks() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_17.md")
+            .write_str("- dentist\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-22",
+            "--skip-future-weeks",
+        ];
+        let mut preparation = preparer(&temp_dir, &args)?;
+        preparation.today = "2024-09-04".parse()?;
+        preparation.run()?;
+
+        // partly past
+        assert!(temp_dir.child("pages/2024___Week 36.md").exists());
+        assert!(!temp_dir.child("pages/2024___Week 37.md").exists());
+        assert!(temp_dir.child("journals/2024_09_10.md").exists());
+        // with notes on one of its days
+        assert!(temp_dir.child("pages/2024___Week 38.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn today_page() -> Result<()> {
+        let temp_dir = graph()?;
+        let today = temp_dir.child("pages/Today.md");
+        let run = |date: &str| -> Result<()> {
+            let args = ["--from", date, "--to", date, "--today-page"];
+            let preparer = Preparer {
+                today_page: Some(date.parse()?),
+                ..preparer(&temp_dir, &args)?
+            };
+            preparer.run()?;
+            Ok(())
+        };
+
+        run("2024-09-02")?;
+        today.assert("-\n- {{embed [[2024-09-02]]}}\n");
+        run("2024-09-03")?;
+        today.assert("-\n- {{embed [[2024-09-03]]}}\n");
+
+        assert!(preparer(&temp_dir, &["--today-page"])?.today_page.is_some());
+        assert!(preparer(&temp_dir, &[])?.today_page.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn preserve_meta() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = ["--from", "2024-09-02", "--to", "2024-09-02"];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let week = temp_dir.child("pages/2024___Week 36.md");
+        let edited = std::fs::read_to_string(&week)?
+            .replace("next:: [[2024/Week 37]]", "next:: [[Holidays]]");
+        week.write_str(&edited)?;
+
+        let preserve = [&args[..], &["--preserve-meta", "next"]].concat();
+        preparer(&temp_dir, &preserve)?.run()?;
+        week.assert(edited.as_str());
+
+        // the other keys are still updated
+        week.write_str(&edited.replace("prev:: [[2024/Week 35]]", "prev:: [[Mine]]"))?;
+        preparer(&temp_dir, &preserve)?.run()?;
+        week.assert(edited.as_str());
+
+        preparer(&temp_dir, &args)?.run()?;
+        let content = std::fs::read_to_string(&week)?;
+        assert!(content.contains("next:: [[2024/Week 37]]\n"), "{content}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn compact() -> Result<()> {
+        let temp_dir = graph()?;
+        let week = Week::new(
+            NaiveDate::from_ymd_opt(2024, 9, 2).unwrap(),
+            WeekStart::Monday,
+        );
+        let path = temp_dir.child("pages/2024___Week 36.md");
+        let blank = |mut page: Page| {
+            page.push_metadata(Filters::default().push("week", false));
+            Ok(page)
+        };
+
+        let compact = preparer(&temp_dir, &["--compact"])?;
+        assert!(!compact.update_overview(path.to_path_buf(), blank)?);
+        assert!(!path.exists());
+        assert!(preparer(&temp_dir, &[])?.update_overview(path.to_path_buf(), blank)?);
+        std::fs::remove_file(&path)?;
+
+        // the pages with embeds are written
+        assert!(compact.print_week(week)?);
+        assert!(path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn day_week_footer() -> Result<()> {
+        let temp_dir = graph()?;
+        let existing = temp_dir.child("journals/2024_09_03.md");
+        existing.write_str("- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-03",
+            "--day-week-footer",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let day = temp_dir.child("journals/2024_09_02.md");
+        let content = std::fs::read_to_string(&day)?;
+        assert!(
+            content.ends_with("-\n- {{embed [[2024/Week 36]]}}\n"),
+            "{content}"
+        );
+        let content_existing = std::fs::read_to_string(&existing)?;
+        assert!(!content_existing.contains("{{embed"), "{content_existing}");
+
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+        day.assert(content.as_str());
+
+        Ok(())
+    }
+
+    #[test]
+    fn title_heading() -> Result<()> {
+        let temp_dir = graph()?;
+        let existing = temp_dir.child("journals/2024_09_02.md");
+        existing.write_str("- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--title-heading",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let day = temp_dir.child("journals/2024_09_01.md");
+        let content = std::fs::read_to_string(&day)?;
+        assert!(content.contains("\n-\n- # 2024-09-01\n"), "{content}");
+        assert_eq!(1, content.matches("# 2024-09-01").count());
+        let content_existing = std::fs::read_to_string(&existing)?;
+        assert!(!content_existing.contains("- #"), "{content_existing}");
+
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+        day.assert(content.as_str());
+
+        // in the title format, once, when both are given
+        let args = [
+            "--from",
+            "2024-09-03",
+            "--to",
+            "2024-09-03",
+            "--title-heading",
+            "--title-format",
+            "%A %-d %B",
+        ];
+        for _ in 0..2 {
+            preparer(&temp_dir, &args)?.run()?;
+            let content = std::fs::read_to_string(temp_dir.child("journals/2024_09_03.md"))?;
+            assert_eq!(
+                1,
+                content.matches("- # Tuesday 3 September\n").count(),
+                "{content}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn on_this_day() -> Result<()> {
+        let temp_dir = graph()?;
+        for year in [2023, 2021, 2020] {
+            temp_dir
+                .child(format!("journals/{year}_09_01.md"))
+                .write_str("- hello\n")?;
+        }
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-01",
+            "--on-this-day",
+            "3",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let day = temp_dir.child("journals/2024_09_01.md");
+        let content = std::fs::read_to_string(&day)?;
+        assert!(
+            content.contains("\n- On this day: [[2023-09-01]] [[2021-09-01]]\n"),
+            "{content}"
+        );
+
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+        day.assert(content.as_str());
+
+        Ok(())
+    }
+
+    #[test]
+    fn recurrence() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_16.md")
+            .write_str("events:: [[Dentist]]\n\n- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-30",
+            "--recurrence",
+            "Review=last friday of month",
+            "--recurrence",
+            "Sync=every other monday",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let events = |day| -> Result<Option<String>> {
+            let page =
+                std::fs::read_to_string(temp_dir.child(format!("journals/2024_09_{day}.md")))?;
+            Ok(page
+                .lines()
+                .find_map(|line| line.strip_prefix("events:: "))
+                .map(str::to_owned))
+        };
+        assert_eq!(Some("[[Review]]".to_owned()), events("27")?);
+        assert_eq!(Some("[[Sync]]".to_owned()), events("02")?);
+        assert_eq!(None, events("09")?);
+        assert_eq!(Some("[[Dentist]], [[Sync]]".to_owned()), events("16")?);
+        assert_eq!(Some("[[Sync]]".to_owned()), events("30")?);
+        assert_eq!(None, events("20")?);
+
+        assert!(preparer(&temp_dir, &["--recurrence", "Sync=every so often"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn carry_tasks() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_01.md")
+            .write_str(indoc! {"
+            - TODO Call the bank
+            - DONE Water the plants
+            - DOING Write the report
+              id:: 66e2a5c4-5ab0-4cfa-a3e4-2b1d8c6b1f10
+              - with the figures
+            - LATER Read a book
+            - Notes about TODO lists
+            - TODOS are not tasks
+        "})?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-30",
+            "--carry-tasks",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let second = temp_dir.child("journals/2024_09_02.md");
+        let content = std::fs::read_to_string(&second)?;
+        assert!(
+            content.ends_with(indoc! {"
+            - TODO Call the bank
+            - DOING Write the report
+              - with the figures
+        "}),
+            "{content}"
+        );
+        assert!(!content.contains("DONE") && !content.contains("LATER"));
+        assert!(!content.contains("id::"));
+
+        // carried once, not again from the pages the run created
+        let carried = args[1].parse::<NaiveDate>()?.iter_days().take(29);
+        let copies = carried
+            .map(|date| {
+                temp_dir
+                    .path()
+                    .join("journals")
+                    .join(date.to_journal_path_name())
+            })
+            .map(std::fs::read_to_string)
+            .collect::<std::io::Result<Vec<_>>>()?
+            .iter()
+            .filter(|content| content.contains("- TODO Call the bank\n"))
+            .count();
+        assert_eq!(1, copies);
+
+        // nor to the existing pages
+        second.write_str(&content.replace("- TODO Call the bank\n", ""))?;
+        preparer(&temp_dir, &args)?.run()?;
+        let content = std::fs::read_to_string(&second)?;
+        assert!(!content.contains("Call the bank"), "{content}");
+
+        // nothing to carry without the previous page
+        let args = [
+            "--from",
+            "2024-08-30",
+            "--to",
+            "2024-08-30",
+            "--carry-tasks",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        let first = std::fs::read_to_string(temp_dir.child("journals/2024_08_30.md"))?;
+        assert!(!first.contains("TODO"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn countdown() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_12_31.md")
+            .write_str("days-left-in-month:: 3\ndays-left-in-year:: 3\n\n- hello\n")?;
+
+        let args = ["--from", "2024-12-30", "--to", "2024-12-31", "--countdown"];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let first = std::fs::read_to_string(temp_dir.child("journals/2024_12_30.md"))?;
+        assert!(first.lines().any(|line| line == "days-left-in-month:: 1"));
+        assert!(first.lines().any(|line| line == "days-left-in-year:: 1"));
+        let last = std::fs::read_to_string(temp_dir.child("journals/2024_12_31.md"))?;
+        assert!(last.lines().any(|line| line == "days-left-in-month:: 0"));
+        assert!(last.lines().any(|line| line == "days-left-in-year:: 0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn journals_disabled() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("logseq/config.edn")
+            .write_str("{:feature/enable-journals? false}")?;
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02"];
+        preparer(&temp_dir, &args)?.run()?;
+        assert_eq!(0, std::fs::read_dir(temp_dir.child("journals"))?.count());
+        assert!(temp_dir.child("pages/2024___Week 35.md").exists());
+
+        preparer(&temp_dir, &[&args[..], &["--force"]].concat())?.run()?;
+        assert_eq!(2, std::fs::read_dir(temp_dir.child("journals"))?.count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn leading_block() -> Result<()> {
+        let temp_dir = graph()?;
+        let week = temp_dir.child("pages/2024___Week 35.md");
+
+        let leads = |args: &[&str]| -> Result<bool> {
+            std::fs::remove_file(week.path()).ok();
+            let args = [&["--from", "2024-09-01", "--to", "2024-09-01"], args].concat();
+            preparer(&temp_dir, &args)?.run()?;
+            let text = std::fs::read_to_string(week.path())?;
+            Ok(text.lines().any(|line| line == "-"))
+        };
+        assert!(leads(&[])?);
+        assert!(leads(&["--leading-block", "always"])?);
+        assert!(!leads(&["--leading-block", "never"])?);
+        assert!(leads(&["--leading-block", "auto"])?);
+
+        let config = temp_dir.child("logseq/config.edn");
+        config.write_str("{:meta/version 1}")?;
+        assert!(leads(&["--leading-block", "auto"])?);
+        config.write_str("{:meta/version 2}")?;
+        assert!(!leads(&["--leading-block", "auto"])?);
+        assert!(leads(&["--leading-block", "always"])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn append_only() -> Result<()> {
+        let temp_dir = graph()?;
+        let week = temp_dir.child("pages/2024___Week 36.md");
+        let existing = indoc! {"
+            - {{embed [[2024-09-03]]}}
+            -   notes,   by hand
+            \t- nested
+
+            - {{embed [[2024-09-02]]}}"};
+        week.write_str(existing)?;
+        // The new days follow, spaced like the existing blocks
+        let appended = (4..=8).fold(existing.to_owned(), |blocks, day| {
+            format!("{blocks}\n\n- {{{{embed [[2024-09-0{day}]]}}}}")
+        }) + "\n";
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-04",
+            "--append-only",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        let content = std::fs::read_to_string(&week)?;
+        let (metadata, blocks) = content.split_at(content.find("- {{embed").unwrap());
+        assert!(metadata.contains("next:: [[2024/Week 37]]"), "{content}");
+        assert_eq!(appended, blocks, "{content}");
+
+        let args = [&args[..], &["--freeze-metadata"]].concat();
+        week.write_str(existing)?;
+        preparer(&temp_dir, &args)?.run()?;
+        week.assert(appended.as_str());
+        preparer(&temp_dir, &args)?.run()?;
+        week.assert(appended.as_str());
+
+        Ok(())
+    }
+
+    #[test]
+    fn stamp_created() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_02.md")
+            .write_str("- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--stamp-created",
+        ];
+        let mut preparer = preparer(&temp_dir, &args)?;
+        preparer.created = Some("2024-09-01T10:00:00Z".to_owned());
+        preparer.run()?;
+
+        let day = temp_dir.child("journals/2024_09_01.md");
+        let content = std::fs::read_to_string(&day)?;
+        assert!(content
+            .lines()
+            .any(|line| line == "created:: 2024-09-01T10:00:00Z"));
+        let existing = std::fs::read_to_string(temp_dir.child("journals/2024_09_02.md"))?;
+        assert!(!existing.contains("created::"));
+
+        preparer.created = Some("2024-10-01T10:00:00Z".to_owned());
+        let summary = preparer.run()?;
+        assert_eq!(summary.updated, 0);
+        day.assert(content.as_str());
+
+        Ok(())
+    }
+
+    #[test]
+    fn stamp_version() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_02.md")
+            .write_str("- hello\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--stamp-version",
+        ];
+        let mut stamped = preparer(&temp_dir, &args)?;
+        assert_eq!(
+            Some(format!(
+                "logseq-journal-prepare/{}",
+                env!("CARGO_PKG_VERSION")
+            )),
+            stamped.prepared_by
+        );
+        stamped.prepared_by = Some("logseq-journal-prepare/1.0.0".to_owned());
+        stamped.run()?;
+
+        let day = temp_dir.child("journals/2024_09_01.md");
+        let content = std::fs::read_to_string(&day)?;
+        assert!(content
+            .lines()
+            .any(|line| line == "prepared-by:: logseq-journal-prepare/1.0.0"));
+        let existing = temp_dir.child("journals/2024_09_02.md");
+        assert!(!std::fs::read_to_string(&existing)?.contains("prepared-by::"));
+
+        stamped.prepared_by = Some("logseq-journal-prepare/2.0.0".to_owned());
+        assert_eq!(0, stamped.run()?.updated);
+        day.assert(content.as_str());
+
+        stamped.restamp = true;
+        stamped.run()?;
+        for page in [&day, &existing] {
+            assert!(std::fs::read_to_string(page)?
+                .lines()
+                .any(|line| line == "prepared-by:: logseq-journal-prepare/2.0.0"));
+        }
+
+        assert!(preparer(&temp_dir, &["--restamp"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn limit() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-30", "--limit", "6"];
+        let summary = preparer(&temp_dir, &args)?.run()?;
+
+        // 2024-09-01 writes 4 pages, 2024-09-02 starts a new week
+        assert_eq!(summary.updated, 6);
+        assert!(temp_dir.child("journals/2024_09_02.md").exists());
+        assert!(temp_dir.child("pages/2024___Week 36.md").exists());
+        assert!(!temp_dir.child("journals/2024_09_03.md").exists());
+
+        let args = ["--from", "2024-09-03", "--to", "2024-09-30", "--limit", "6"];
+        let summary = preparer(&temp_dir, &args)?.run()?;
+        // the overviews are already up to date
+        assert_eq!(summary.updated, 6);
+        assert!(temp_dir.child("journals/2024_09_08.md").exists());
+        assert!(!temp_dir.child("journals/2024_09_09.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn flat() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("README.md").write_str("# Notes\n")?;
+
+        // other markdown files aren't pages of a graph
+        let args = ["--from", "2024-09-01", "--to", "2024-09-01"];
+        let error = preparer(&temp_dir, &args).unwrap_err();
+        assert!(
+            matches!(error.downcast_ref(), Some(PrepareError::NotAGraph { .. })),
+            "{error}"
+        );
+
+        temp_dir.child("notes.md").write_str("- notes\n")?;
+        temp_dir.child("2024.md").write_str("- Plans\n")?;
+        let flat = preparer(&temp_dir, &args)?;
+        assert!(flat.flat);
+        flat.run()?;
+
+        let journal = std::fs::read_to_string(temp_dir.child("2024_09_01.md"))?;
+        assert!(journal.contains("[[2024/Week 35]]"));
+        assert!(temp_dir.child("2024___Week 35.md").exists());
+        assert!(temp_dir.child("2024___September.md").exists());
+        assert!(!temp_dir.child("journals").exists());
+        assert!(!temp_dir.child("pages").exists());
+
+        // prepared pages outside of the range are found among the others
+        let args = [
+            "--from",
+            "2024-10-01",
+            "--to",
+            "2024-10-01",
+            "--prune",
+            "--yes",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("2024_09_01.md").exists());
+        assert!(temp_dir.child("notes.md").exists());
+        assert!(temp_dir.child("README.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn extensions() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-01",
+            "--journal-extension",
+            "markdown",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(temp_dir.child("journals/2024_09_01.markdown").exists());
+        assert!(!temp_dir.child("journals/2024_09_01.md").exists());
+        let week = std::fs::read_to_string(temp_dir.child("pages/2024___Week 35.md"))?;
+        assert!(week.contains("[[2024-09-01]]"));
+
+        // the existing pages are found with their extension
+        assert_eq!(preparer(&temp_dir, &args)?.run()?.updated, 0);
+        let args = [
+            "--from",
+            "2024-10-01",
+            "--to",
+            "2024-10-01",
+            "--journal-extension",
+            "markdown",
+            "--page-extension",
+            "markdown",
+            "--prune",
+            "--yes",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("journals/2024_09_01.markdown").exists());
+        assert!(temp_dir.child("pages/2024___Week 35.md").exists());
+
+        // the pages are only written in markdown
+        assert!(preparer(&temp_dir, &["--journal-extension", "org"]).is_err());
+        assert!(preparer(&temp_dir, &["--page-extension", "txt"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn journal_partition() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = [
+            "--from",
+            "2024-12-31",
+            "--to",
+            "2025-01-01",
+            "--journal-partition",
+            "year",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(temp_dir.child("journals/2024/2024_12_31.md").exists());
+        assert!(temp_dir.child("journals/2025/2025_01_01.md").exists());
+        assert!(!temp_dir.child("journals/2024_12_31.md").exists());
+        let week = std::fs::read_to_string(temp_dir.child("pages/2025___Week 01.md"))?;
+        assert!(week.contains("- {{embed [[2024-12-31]]}}\n"), "{week}");
+        assert!(week.contains("- {{embed [[2025-01-01]]}}\n"), "{week}");
+
+        // the existing pages are found in their partition
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+
+        // or outside of it, when written before partitioning
+        let day = temp_dir.child("journals/2025_01_02.md");
+        day.write_str("- notes\n")?;
+        let args = [
+            "--from",
+            "2025-01-02",
+            "--to",
+            "2025-01-02",
+            "--journal-partition",
+            "year",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("journals/2025/2025_01_02.md").exists());
+        let content = std::fs::read_to_string(&day)?;
+        assert!(content.contains("- notes\n"), "{content}");
+        assert!(content.contains("day:: Thursday\n"), "{content}");
+
+        let args = [
+            "--from",
+            "2025-03-01",
+            "--to",
+            "2025-03-01",
+            "--journal-partition",
+            "month",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(temp_dir.child("journals/2025/03/2025_03_01.md").exists());
+        let args = [
+            "--from",
+            "2025-03-01",
+            "--to",
+            "2025-03-01",
+            "--journal-partition",
+            "year",
+            "--prune",
+            "--yes",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("journals/2025/2025_01_01.md").exists());
+        assert!(temp_dir.child("journals/2025/03/2025_03_01.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sidecar() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_01.md")
+            .write_str("- notes\n")?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-01",
+            "--sidecar",
+            "json",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        // in sync with the parsed page
+        for name in ["journals/2024_09_01", "pages/2024___Week 35", "pages/2024"] {
+            let path = temp_dir.child(format!("{name}.md"));
+            let page = Page::try_from(path.path())?;
+            temp_dir
+                .child(format!("{name}.json"))
+                .assert(page.sidecar().to_string());
+        }
+        let sidecar = std::fs::read_to_string(temp_dir.child("journals/2024_09_01.json"))?;
+        assert!(
+            sidecar.contains("\"week\": \"[[2024/Week 35]]\""),
+            "{sidecar}"
+        );
+        assert!(sidecar.contains("\"- notes\""), "{sidecar}");
+
+        // written again when missing, even if the page is up to date
+        std::fs::remove_file(temp_dir.child("journals/2024_09_01.json"))?;
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+        temp_dir
+            .child("journals/2024_09_01.json")
+            .assert(sidecar.as_str());
+
+        // with the existing blocks of an appended page
+        let day = temp_dir.child("journals/2024_09_02.md");
+        day.write_str("- notes\n")?;
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "--sidecar",
+            "json",
+            "--append-only",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        let page = Page::try_from(day.path())?;
+        let sidecar = std::fs::read_to_string(temp_dir.child("journals/2024_09_02.json"))?;
+        assert_eq!(page.sidecar().to_string(), sidecar);
+        assert!(sidecar.contains("\"- notes\""), "{sidecar}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn title_separator() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "--title-separator",
+            " · ",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        // the files stay in the namespace, the titles use the separator
+        let read = |name| std::fs::read_to_string(temp_dir.child(name));
+        let week = read("pages/2024___Week 36.md")?;
+        assert!(week.contains("title:: 2024 · Week 36\n"), "{week}");
+        assert!(week.contains("month:: [[2024 · September]]\n"), "{week}");
+        assert!(week.contains("next:: [[2024 · Week 37]]\n"), "{week}");
+        let month = read("pages/2024___September.md")?;
+        assert!(month.contains("title:: 2024 · September\n"), "{month}");
+        let year = read("pages/2024.md")?;
+        assert!(!year.contains("title::"), "{year}");
+        assert!(year.contains("- [[2024 · September]]\n"), "{year}");
+
+        // and so do the links
+        let day = read("journals/2024_09_02.md")?;
+        assert!(day.contains("week:: [[2024 · Week 36]]\n"), "{day}");
+        assert!(day.contains(r#""2024 · Week 36" false"#), "{day}");
+        for page in [week, month, year, day] {
+            assert!(!page.contains("2024/"), "{page}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn single_day() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-01"];
+        let summary = preparer(&temp_dir, &args)?.run()?;
+
+        assert_eq!(summary.updated, 4);
+        assert!(temp_dir.child("journals/2024_09_01.md").exists());
+        assert!(!temp_dir.child("journals/2024_09_02.md").exists());
+        assert!(temp_dir.child("pages/2024___Week 35.md").exists());
+        assert!(temp_dir.child("pages/2024___September.md").exists());
+        assert!(temp_dir.child("pages/2024.md").exists());
+
+        assert!(preparer(&temp_dir, &["--from", "2024-09-02", "--to", "2024-09-01"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn embed_target() -> Result<()> {
+        let temp_dir = graph()?;
+        // the first block after the empty one this tool writes
+        temp_dir
+            .child("journals/2024_09_03.md")
+            .write_str("-\n- Morning\n  id:: 66d4a5e1-2bd5-4b5e-9a1f-8c4e0f1d2a3b\n")?;
+
+        let args = ["--from", "2024-09-02", "--to", "2024-09-08"];
+        let block = [&args[..], &["--embed-target", "block"]].concat();
+        preparer(&temp_dir, &block)?.run()?;
+
+        let read_week = || std::fs::read_to_string(temp_dir.child("pages/2024___Week 36.md"));
+        let week = read_week()?;
+        assert!(week.contains("- {{embed [[2024-09-02]]}}\n"));
+        assert!(week.contains("- {{embed ((66d4a5e1-2bd5-4b5e-9a1f-8c4e0f1d2a3b))}}\n"));
+        assert!(!week.contains("[[2024-09-03]]"));
+
+        // unmarked, the embed of the block could be written by hand
+        preparer(&temp_dir, &args)?.run()?;
+        let week = read_week()?;
+        assert!(week.contains("((66d4a5e1"), "{week}");
+
+        // switching the target replaces the marked embed instead of adding
+        // one
+        let marked = [&args[..], &["--mark-generated"]].concat();
+        let marked_block = [&block[..], &["--mark-generated"]].concat();
+        temp_dir.child("pages/2024___Week 36.md").write_str("")?;
+        preparer(&temp_dir, &marked_block)?.run()?;
+        preparer(&temp_dir, &marked)?.run()?;
+        let week = read_week()?;
+        assert!(week.contains("- {{embed [[2024-09-03]]}}\n"), "{week}");
+        assert!(!week.contains("((66d4a5e1"), "{week}");
+        preparer(&temp_dir, &marked_block)?.run()?;
+        let week = read_week()?;
+        assert!(week.contains("((66d4a5e1"), "{week}");
+        assert!(!week.contains("[[2024-09-03]]"), "{week}");
+
+        // the day pages aren't read for the page target
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_04.md")
+            .write_str("filters:: {\"2024/September\" maybe}\n\n-\n")?;
+        let day = ["--from", "2024-09-02", "--to", "2024-09-02"];
+        preparer(&temp_dir, &day)?.run()?;
+        let error = preparer(
+            &temp_dir,
+            &[&day[..], &["--embed-target", "block"]].concat(),
+        )?
+        .run()
+        .unwrap_err();
+        assert!(
+            matches!(error.downcast_ref(), Some(PrepareError::Parse { .. })),
+            "{error:#}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn name_filter() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02"];
+        let summary = preparer(
+            &temp_dir,
+            &[&args[..], &["--name-filter", "*/Week *"]].concat(),
+        )?
+        .run()?;
+        assert_eq!(summary.updated, 2);
+        assert!(temp_dir.child("pages/2024___Week 35.md").exists());
+        assert!(temp_dir.child("pages/2024___Week 36.md").exists());
+        assert!(!temp_dir.child("pages/2024___September.md").exists());
+        assert!(!temp_dir.child("journals/2024_09_01.md").exists());
+
+        let filters = ["--name-filter", "2024", "--name-filter", "2024-09-0*"];
+        let summary = preparer(&temp_dir, &[&args[..], &filters].concat())?.run()?;
+        assert_eq!(summary.updated, 3);
+        assert!(temp_dir.child("pages/2024.md").exists());
+        assert!(temp_dir.child("journals/2024_09_02.md").exists());
+        assert!(!temp_dir.child("pages/2024___September.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn resume() -> Result<()> {
+        let temp_dir = graph()?;
+        let state = temp_dir.child(".logseq-journal-prepare");
+
+        // Interrupted after 2024-09-02
+        let args = ["--from", "2024-09-01", "--to", "2024-09-10"];
+        preparer(&temp_dir, &[&args[..], &["--limit", "5"]].concat())?.run()?;
+        state.assert("from:: 2024-09-01\nto:: 2024-09-10\ndone:: 2024-09-02\n");
+
+        // Days before the last written one are not prepared again
+        std::fs::remove_file(temp_dir.child("journals/2024_09_01.md"))?;
+        preparer(&temp_dir, &[&args[..], &["--resume"]].concat())?.run()?;
+        assert!(!temp_dir.child("journals/2024_09_01.md").exists());
+        assert!(temp_dir.child("journals/2024_09_10.md").exists());
+        assert!(!state.exists());
+
+        // The last written day is prepared again if it's not up to date
+        preparer(&temp_dir, &[&args[..], &["--limit", "1"]].concat())?.run()?;
+        state.assert("from:: 2024-09-01\nto:: 2024-09-10\ndone:: 2024-09-01\n");
+        temp_dir.child("journals/2024_09_01.md").write_str("")?;
+        preparer(&temp_dir, &[&args[..], &["--resume"]].concat())?.run()?;
+        assert_ne!(
+            "",
+            std::fs::read_to_string(temp_dir.child("journals/2024_09_01.md"))?
+        );
+
+        // Without a state, or for another range, the whole range is prepared
+        std::fs::remove_file(temp_dir.child("journals/2024_09_01.md"))?;
+        preparer(&temp_dir, &[&args[..], &["--resume"]].concat())?.run()?;
+        assert!(temp_dir.child("journals/2024_09_01.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn week_year() -> Result<()> {
+        for (week_year, name, next) in [
+            ("iso", "2025___Week 01.md", "[[2025/Week 02]]"),
+            ("calendar", "2024___Week 53.md", "[[2025/Week 02]]"),
+        ] {
+            let temp_dir = graph()?;
+
+            let args = [
+                "--from",
+                "2024-12-30",
+                "--to",
+                "2025-01-05",
+                "--week-year",
+                week_year,
+            ];
+            preparer(&temp_dir, &args)?.run()?;
+
+            let week = std::fs::read_to_string(temp_dir.child("pages").child(name))?;
+            assert!(week.contains(&format!("next:: {next}\n")), "{week}");
+            assert!(week.contains("month:: [[2024/December]]\n"), "{week}");
+            let day = std::fs::read_to_string(temp_dir.child("journals/2025_01_05.md"))?;
+            let link = name.strip_suffix(".md").unwrap().replace("___", "/");
+            assert!(day.contains(&format!("week:: [[{link}]]")), "{day}");
+
+            // pruned with the same week-year
+            let args = [
+                "--from",
+                "2025-02-03",
+                "--to",
+                "2025-02-09",
+                "--week-year",
+                week_year,
+                "--prune",
+            ];
+            let plan = preparer(&temp_dir, &args)?.prune_plan()?;
+            let path = temp_dir.child("pages").child(name).to_path_buf();
+            assert!(plan.iter().any(|(pruned, _)| *pruned == path), "{plan:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn months() -> Result<()> {
+        let temp_dir = graph()?;
+        let args = ["--months", "2024/November,2024/09"];
+        let months = preparer(&temp_dir, &args)?;
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 9, 1).unwrap(), months.from);
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 11, 30).unwrap(), months.to);
+
+        let summary = months.run()?;
+        let journals = temp_dir.child("journals");
+        for day in ["2024_09_01", "2024_09_30", "2024_11_01", "2024_11_30"] {
+            assert!(journals.child(format!("{day}.md")).exists(), "{day}");
+        }
+        for day in ["2024_08_31", "2024_10_01", "2024_10_31", "2024_12_01"] {
+            assert!(!journals.child(format!("{day}.md")).exists(), "{day}");
+        }
+        let pages = temp_dir.child("pages");
+        assert!(pages.child("2024___September.md").exists());
+        assert!(pages.child("2024___November.md").exists());
+        assert!(!pages.child("2024___October.md").exists());
+        // the week across September and October is still whole
+        let week = std::fs::read_to_string(pages.child("2024___Week 40.md"))?;
+        assert!(week.contains("[[2024-10-06]]"), "{week}");
+        assert_eq!(30 + 30, std::fs::read_dir(journals.path())?.count());
+        assert!(summary.updated > 60);
+
+        assert!(preparer(&temp_dir, &["--months", "2024/Sept"]).is_err());
+        assert!(preparer(&temp_dir, &["--months", "2024/09", "--from", "2024-09-01"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn week_range() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("journals").create_dir_all()?;
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        let range = preparer(
+            &temp_dir,
+            &["--from-week", "2024-W36", "--to-week", "2024-W40"],
+        )?;
+        assert_eq!(date(2024, 9, 2), range.from);
+        assert_eq!(date(2024, 10, 6), range.to);
+
+        // across the year, 2025-W01 starting in 2024
+        let range = preparer(
+            &temp_dir,
+            &["--from-week", "2024-W52", "--to-week", "2025-W01"],
+        )?;
+        assert_eq!(date(2024, 12, 23), range.from);
+        assert_eq!(date(2025, 1, 5), range.to);
+
+        // not along the dates
+        assert!(preparer(
+            &temp_dir,
+            &["--from-week", "2025-W01", "--to", "2025-01-01"]
+        )
+        .is_err());
+        assert!(preparer(
+            &temp_dir,
+            &["--from", "2025-01-01", "--to-week", "2025-W01"]
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn horizon() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("journals").create_dir_all()?;
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        for (horizon, to) in [
+            (None, date(2024, 9, 30)),
+            (Some("week"), date(2024, 9, 7)),
+            (Some("month"), date(2024, 9, 30)),
+            (Some("quarter"), date(2024, 11, 30)),
+            (Some("year"), date(2025, 8, 31)),
+        ] {
+            let mut args = vec!["--from", "2024-08-31"];
+            args.extend(horizon.iter().flat_map(|horizon| ["--horizon", horizon]));
+            assert_eq!(to, preparer(&temp_dir, &args)?.to, "{horizon:?}");
+        }
+
+        let args = [
+            "--from",
+            "2024-08-31",
+            "--to",
+            "2024-09-01",
+            "--horizon",
+            "year",
+        ];
+        assert_eq!(date(2024, 9, 1), preparer(&temp_dir, &args)?.to);
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02"];
+        let Err(error) = preparer(&temp_dir, &args) else {
+            panic!("{:?} is a graph", temp_dir.path());
+        };
+        assert!(matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::NotAGraph { .. })
+        ));
+
+        temp_dir.child("journals").create_dir_all()?;
+        let Err(error) = preparer(&temp_dir, &["--from", "2024-09-02", "--to", "2024-09-01"])
+        else {
+            panic!("2024-09-02 is before 2024-09-01");
+        };
+        assert!(matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::DateRangeInvalid { .. })
+        ));
+
+        temp_dir
+            .child("journals/2024_09_01.md")
+            .write_str("filters:: {\"2024/Week 35\"}\n")?;
+        let error = preparer(&temp_dir, &args)?.run().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::Parse { line: 1, .. })
+        ));
+
+        // a malformed filters:: is reported rather than merged
+        let malformed = "day:: Sunday\nfilters:: {\"2024/Week 35\" no}\n\n- notes\n";
+        temp_dir
+            .child("journals/2024_09_01.md")
+            .write_str(malformed)?;
+        let error = preparer(&temp_dir, &args)?.run().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PrepareError>(),
+            Some(PrepareError::Parse { line: 2, .. })
+        ));
+        assert!(
+            error.to_string().contains("Expected true or false"),
+            "{error}"
+        );
+        temp_dir.child("journals/2024_09_01.md").assert(malformed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn month_week_index() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-01",
+            "--month-week-index",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        // the weeks 35 and 40 are shared with August and October
+        let month = temp_dir.child("pages/2024___September.md");
+        let content = std::fs::read_to_string(&month)?;
+        let index = (35..=40)
+            .map(|week| format!("[[2024/Week {week}]]"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert!(
+            content.contains(&format!("\n-\n- {index}\n- {{{{embed [[2024-09-01]]}}}}\n")),
+            "{content}"
+        );
+
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+        month.assert(content.as_str());
+
+        // weeks starting on Sunday
+        let args = [&args[..], &["--week-start", "sunday"]].concat();
+        let month = preparer(&temp_dir, &args)?;
+        assert_eq!(
+            vec![(2024, 9, 1), (2024, 9, 29)],
+            [0, 4].map(|index| {
+                let first = month.month_weeks(Month::from(month.from))[index].first();
+                (first.year(), first.month(), first.day())
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn month_layout() -> Result<()> {
+        let temp_dir = graph()?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-30",
+            "--month-layout",
+            "by-week",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+
+        let month = std::fs::read_to_string(temp_dir.child("pages/2024___September.md"))?;
+        // Sunday 2024-09-01 ends the week 35, started in August
+        assert!(month.contains(indoc! {"
+            -
+            - [[2024/Week 35]]
+              - {{embed [[2024-09-01]]}}
+            - [[2024/Week 36]]
+              - {{embed [[2024-09-02]]}}
+        "}));
+        assert!(month.ends_with(indoc! {"
+              - {{embed [[2024-09-29]]}}
+            - [[2024/Week 40]]
+              - {{embed [[2024-09-30]]}}
+        "}));
+        assert_eq!(6, month.matches("\n- [[2024/Week ").count());
+        assert_eq!(30, month.matches("\n  - {{embed ").count());
+
+        let summary = preparer(&temp_dir, &args)?.run()?;
+        assert_eq!(summary.updated, 0);
+
+        Ok(())
     }
 }
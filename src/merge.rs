@@ -0,0 +1,110 @@
+use crate::page::Page;
+use crate::{Preparer, Summary};
+use anyhow::Result;
+use std::path::Path;
+
+impl Preparer {
+    /// Merge the pages of the range of the graph at `source` into the ones
+    /// with the same file names, keeping the metadata of this graph when
+    /// both have a different text
+    pub(crate) fn merge_graphs(&self, source: &Path) -> Result<Summary> {
+        let mut summary = Summary::default();
+
+        for path in self.page_paths() {
+            let Ok(name) = path.strip_prefix(&self.path) else {
+                continue;
+            };
+            let source_path = source.join(name);
+            if !source_path.exists() {
+                continue;
+            }
+
+            let theirs = Page::try_from(source_path.as_path())?;
+            let before = match path.exists() {
+                true => self.pages.get(&path)?,
+                false => self.new_page(&path),
+            };
+            let conflicts = before.conflicts(&theirs);
+            for (key, ours, other) in &conflicts {
+                log::warn!(
+                    "Conflicting {key}:: in {}, keeping {ours:?} over {other:?}",
+                    path.display()
+                );
+            }
+            let keys = conflicts
+                .into_iter()
+                .map(|(key, ..)| key)
+                .collect::<Vec<_>>();
+            let mut page = before.clone() + theirs.preserve_metadata(&keys);
+
+            let updated = page.is_modified();
+            if updated {
+                log::info!("Merging page {}", source_path.display());
+                self.write_page(&before, &mut page, &path)?;
+            }
+            summary.record(updated);
+        }
+
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{graph, preparer};
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn merge_graphs() -> Result<()> {
+        let temp_dir = graph()?;
+        let source = graph()?;
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-02",
+            "--mark-generated",
+        ];
+        preparer(&temp_dir, &args)?.run()?;
+        preparer(&source, &args)?.run()?;
+
+        let day = temp_dir.child("journals/2024_09_01.md");
+        let text = std::fs::read_to_string(day.path())?;
+        day.write_str(&format!("mood:: calm\n{text}- personal notes\n- lunch\n"))?;
+        source.child("journals/2024_09_01.md").write_str(&format!(
+            "mood:: busy\ntags:: work\n{text}- lunch\n- work notes\n"
+        ))?;
+        std::fs::remove_file(temp_dir.child("journals/2024_09_02.md"))?;
+        source
+            .child("journals/2024_09_02.md")
+            .write_str("- only at work\n")?;
+
+        let source_path = source.path().to_str().unwrap();
+        let mut merge = preparer(
+            &temp_dir,
+            &[&args[..], &["--merge-from", source_path]].concat(),
+        )?;
+        let summary = merge.run()?;
+        // the other pages are the same in both graphs
+        assert_eq!(2, summary.updated);
+
+        // the metadata unioned, keeping ours on conflict, the blocks deduped
+        let merged = std::fs::read_to_string(day.path())?;
+        assert!(merged.starts_with("mood:: calm\n"), "{merged}");
+        assert!(merged.contains("\ntags:: work\n"), "{merged}");
+        assert!(
+            merged.ends_with("- personal notes\n- lunch\n- work notes\n"),
+            "{merged}"
+        );
+        // missing here, taken from the source
+        temp_dir
+            .child("journals/2024_09_02.md")
+            .assert("-\n- only at work\n");
+
+        merge.dry_run = true;
+        assert_eq!(0, merge.run()?.updated);
+
+        Ok(())
+    }
+}
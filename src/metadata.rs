@@ -1,13 +1,43 @@
-use anyhow::{Context, Result};
+use crate::error::PrepareError;
+use anyhow::Result;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::ops::Add;
 use std::str::FromStr;
 
 lazy_static! {
-    static ref REGEX: Regex =
-        Regex::new(r#"^\s*"(?<word>[^"]+)"\s*(?<boolean>true|false)\s*$"#).unwrap();
+    static ref LINK: Regex = Regex::new(r"\[\[(?<name>[^\]]+)\]\]").unwrap();
+}
+
+/// Merge policy of the metadata keys, others being replaced
+const POLICIES: [(&str, Policy); 6] = [
+    ("created", Policy::CreateOnly),
+    ("prepared-by", Policy::CreateOnly),
+    ("filters", Policy::Union),
+    ("alias", Policy::Union),
+    ("tags", Policy::Union),
+    ("events", Policy::Union),
+];
+
+/// How a metadata of an existing page is merged with the newly generated one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Policy {
+    /// Take the new value
+    Replace,
+    /// Only set on new pages, kept as is afterwards
+    CreateOnly,
+    /// Merge the filters or the list items of both values
+    Union,
+}
+
+impl Policy {
+    pub fn of(key: &str) -> Self {
+        POLICIES
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(Policy::Replace, |(_, policy)| *policy)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, derive_more::Display)]
@@ -15,32 +45,75 @@ lazy_static! {
 pub enum Value {
     Text(String),
     Filters(Filters),
+    List(List),
 }
 
+/// The pages filtered in or out of the linked references, by their exact
+/// name: the keys are never normalized, `DONE` and `done` being two pages
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct Filters(HashMap<String, bool>);
+pub struct Filters(BTreeMap<String, bool>);
 
 impl Filters {
     pub fn push<S: Into<String>>(mut self, key: S, value: bool) -> Self {
         self.0.insert(key.into(), value);
         self
     }
+
+    /// Rename the filtered pages, `rename` giving the new name of a page,
+    /// if any
+    pub fn rename<F>(&mut self, rename: F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        self.0 = std::mem::take(&mut self.0)
+            .into_iter()
+            .map(|(key, value)| (rename(&key).unwrap_or(key), value))
+            .collect();
+    }
 }
 
+/// Parse the EDN map of logseq's `filters::`, e.g. `{"month" false, "week"
+/// true}`, the commas being optional like in EDN
 impl FromStr for Filters {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let mut filters = HashMap::<String, bool>::new();
+        let Some(map) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) else {
+            anyhow::bail!("Expected a map between {{ and }}, found {:?}", s);
+        };
 
-        let s = &s[1..s.len() - 1];
+        let mut filters = BTreeMap::<String, bool>::new();
+        let mut chars = map.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+            match chars.next() {
+                None => break,
+                Some('"') => {}
+                Some(c) => anyhow::bail!("Expected a quoted page name, found {:?}", c),
+            }
+
+            let mut word = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => word.extend(chars.next()),
+                    Some(c) => word.push(c),
+                    None => anyhow::bail!("Unterminated page name {:?}", word),
+                }
+            }
 
-        for part in s.split(",") {
-            let Some((_, [word, boolean])) = REGEX.captures(part).map(|caps| caps.extract()) else {
-                anyhow::bail!("Cannot parse {:?} with regex", part);
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let mut boolean = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                boolean.push(c);
+            }
+            let boolean = match boolean.as_str() {
+                "true" => true,
+                "false" => false,
+                _ => anyhow::bail!("Expected true or false after {:?}", word),
             };
 
-            filters.insert(word.to_owned(), boolean.parse::<bool>()?);
+            filters.insert(word, boolean);
         }
 
         Ok(Self(filters))
@@ -54,7 +127,10 @@ impl std::fmt::Display for Filters {
             "{{{}}}",
             self.0
                 .iter()
-                .map(|(key, b)| format!(r#""{key}" {b}"#))
+                .map(|(key, b)| {
+                    let key = key.replace('\\', r"\\").replace('"', r#"\""#);
+                    format!(r#""{key}" {b}"#)
+                })
                 .collect::<Vec<_>>()
                 .join(", ")
         )
@@ -73,6 +149,158 @@ impl Add for Filters {
     }
 }
 
+/// `s` as a JSON string, quoted and escaped
+pub fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            '\n' => json.push_str(r"\n"),
+            '\r' => json.push_str(r"\r"),
+            '\t' => json.push_str(r"\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl Value {
+    /// The value as JSON: a string, an object of booleans for the filters
+    /// or an array of strings for the lists
+    pub fn to_json(&self) -> String {
+        let join = |items: Vec<String>| items.join(", ");
+        match self {
+            Value::Text(text) => json_string(text),
+            Value::Filters(filters) => format!(
+                "{{{}}}",
+                join(
+                    filters
+                        .0
+                        .iter()
+                        .map(|(key, value)| format!("{}: {value}", json_string(key)))
+                        .collect()
+                )
+            ),
+            Value::List(list) => format!("[{}]", join(list.names().map(json_string).collect())),
+        }
+    }
+}
+
+/// Comma-separated list of page references, e.g. `[[Sep 1, 2024]], #work,
+/// journal`. The items are kept as written, with their spacing, the added
+/// ones being written as links
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct List(Vec<String>);
+
+/// Name of the page an item of a list refers to, e.g. `work` for `#work`,
+/// `[[work]]` or `work`
+fn item_name(item: &str) -> &str {
+    let item = item.trim();
+    let item = item.strip_prefix('#').unwrap_or(item);
+    item.strip_prefix("[[")
+        .and_then(|item| item.strip_suffix("]]"))
+        .unwrap_or(item)
+}
+
+impl List {
+    /// Add a link to the page `name`, unless an item already refers to it
+    pub fn push<S: Into<String>>(self, name: S) -> Self {
+        let name = name.into();
+        self.push_item(&format!("[[{name}]]"))
+    }
+
+    /// Add `item` as written, unless an item already refers to its page
+    fn push_item(mut self, item: &str) -> Self {
+        let item = item.trim();
+        if self.0.iter().all(|i| item_name(i) != item_name(item)) {
+            match self.0.is_empty() {
+                true => self.0.push(item.to_owned()),
+                false => self.0.push(format!(" {item}")),
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Names of the pages the items refer to
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|item| item_name(item))
+    }
+
+    /// The list as the value of `key`
+    pub fn into_metadata<K: Into<String>>(self, key: K) -> Metadata {
+        Metadata {
+            key: key.into(),
+            value: Value::List(self),
+        }
+    }
+
+    /// Sort the items alphabetically by page name, regardless of their case
+    pub fn sort(&mut self) {
+        let mut items = std::mem::take(&mut self.0);
+        items.sort_by_cached_key(|item| item_name(item).to_lowercase());
+        *self = items
+            .iter()
+            .fold(List::default(), |list, item| list.push_item(item));
+    }
+}
+
+/// Split on the commas outside of the links, which page names may contain
+impl FromStr for List {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut items = Vec::new();
+        let (mut item, mut depth) = (String::new(), 0);
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '[' | ']' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    depth = match c {
+                        '[' => depth + 1,
+                        _ => depth.max(1) - 1,
+                    };
+                    item.push(c);
+                    item.push(c);
+                }
+                ',' if depth == 0 => items.push(std::mem::take(&mut item)),
+                c => item.push(c),
+            }
+        }
+        items.push(item);
+
+        let mut items = items
+            .into_iter()
+            .filter(|item| !item.trim().is_empty())
+            .collect::<Vec<_>>();
+        if let Some(first) = items.first_mut() {
+            *first = first.trim_start().to_owned();
+        }
+        Ok(Self(items))
+    }
+}
+
+impl std::fmt::Display for List {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0.join(","))
+    }
+}
+
+impl Add for List {
+    type Output = List;
+
+    fn add(self, rhs: List) -> Self::Output {
+        rhs.0.iter().fold(self, |list, item| list.push_item(item))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, derive_more::Display)]
 #[display("{key}:: {value}")]
 pub struct Metadata {
@@ -81,23 +309,42 @@ pub struct Metadata {
 }
 
 impl Metadata {
+    pub fn policy(&self) -> Policy {
+        Policy::of(&self.key)
+    }
+
+    /// Sort the items of a list value, see [`List::sort`]
+    pub fn sort_list(&mut self) {
+        if let Value::List(list) = &mut self.value {
+            list.sort();
+        }
+    }
+
     pub fn update(&mut self, rhs: Metadata) {
-        if self.key == rhs.key {
-            self.value = match (self.value.clone(), rhs.value) {
-                (Value::Filters(f), Value::Filters(g)) => Value::Filters(f + g),
-                (_, v) => v,
-            }
+        if self.key != rhs.key {
+            return;
+        }
+
+        self.value = match (self.policy(), self.value.clone(), rhs.value) {
+            (Policy::CreateOnly, v, _) => v,
+            (Policy::Union, Value::Filters(f), Value::Filters(g)) => Value::Filters(f + g),
+            (Policy::Union, Value::List(l), Value::List(m)) => Value::List(l + m),
+            (_, _, v) => v,
         }
     }
 }
 
 impl FromStr for Metadata {
-    type Err = anyhow::Error;
+    type Err = PrepareError;
 
-    fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
         let Some((key, value)) = s.split_once("::") else {
-            anyhow::bail!("Can't find :: in metadata {:?}", s);
+            return Err(PrepareError::parse(s, "Can't find :: in metadata"));
         };
+        // logseq properties hold a single line, the next ones being blocks
+        if value.contains('\n') {
+            return Err(PrepareError::parse(s, "A metadata value can't span lines"));
+        }
 
         let key = key.trim();
         match key {
@@ -107,7 +354,16 @@ impl FromStr for Metadata {
                     value
                         .trim()
                         .parse()
-                        .with_context(|| format!("Parsing metadata {:?}", s))?,
+                        .map_err(|e| PrepareError::parse(s, format!("{e:#}")))?,
+                ),
+            }),
+            _ if Policy::of(key) == Policy::Union => Ok(Self {
+                key: key.to_owned(),
+                value: Value::List(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|e| PrepareError::parse(s, format!("{e:#}")))?,
                 ),
             }),
             _ => Ok(Self {
@@ -127,6 +383,12 @@ impl From<Filters> for Metadata {
     }
 }
 
+impl From<List> for Metadata {
+    fn from(l: List) -> Self {
+        l.into_metadata("alias")
+    }
+}
+
 pub trait ToMetadata {
     fn to_metadata<K: Into<String>>(&self, key: K) -> Metadata;
 }
@@ -140,9 +402,20 @@ impl<V: ToString> ToMetadata for V {
 }
 
 #[cfg(test)]
+// the failures show the value of the filters
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn multiline() {
+        let error = "title:: Monday\nand Tuesday"
+            .parse::<Metadata>()
+            .unwrap_err();
+        assert!(matches!(error, PrepareError::Parse { .. }), "{error:?}");
+        assert!(error.to_string().contains("can't span lines"), "{error}");
+    }
+
     #[test]
     fn value_display() {
         assert_eq!("foo".to_owned(), Value::Text("foo".to_owned()).to_string());
@@ -182,6 +455,74 @@ mod tests {
         assert!(result.as_str() == s || result.as_str() == alt);
     }
 
+    #[test]
+    fn filters_keys() -> anyhow::Result<()> {
+        let filters = Filters::default()
+            .push("in-progress", true)
+            .push("DONE", false)
+            .push("done", true)
+            .push("2024/Week 36", false);
+        let edn = r#"{"2024/Week 36" false, "DONE" false, "done" true, "in-progress" true}"#;
+        assert_eq!(edn, filters.to_string());
+        assert_eq!(filters, edn.parse()?);
+
+        // through the metadata, and merged with other keys
+        let metadata = format!("filters:: {edn}").parse::<Metadata>()?;
+        assert_eq!(format!("filters:: {edn}"), metadata.to_string());
+        let mut merged = Metadata::from(Filters::default().push("In-Progress", false));
+        merged.update(metadata);
+        let Value::Filters(merged) = merged.value else {
+            panic!("{merged:?} are not filters");
+        };
+        assert_eq!(5, merged.0.len());
+        assert!(!merged.0["In-Progress"]);
+        assert!(merged.0["in-progress"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn filters_edn() -> anyhow::Result<()> {
+        let filters = Filters::default()
+            .push("2024/Week 36", false)
+            .push("a, b", true)
+            .push(r#"say "hi""#, false);
+        let edn = filters.to_string();
+        assert_eq!(
+            r#"{"2024/Week 36" false, "a, b" true, "say \"hi\"" false}"#,
+            edn
+        );
+        assert_eq!(filters, edn.parse()?);
+
+        // the commas are whitespace in EDN
+        assert_eq!(
+            Filters::default().push("a", true).push("b", false),
+            r#"{"a" true "b" false}"#.parse()?
+        );
+        assert_eq!(
+            Filters::default().push("a", true),
+            r#"{ "a"  true , }"#.parse()?
+        );
+        assert_eq!(Filters::default(), "{}".parse()?);
+        assert_eq!("{}", Filters::default().to_string());
+
+        for malformed in [
+            "",
+            "{",
+            r#"{"a" true"#,
+            r#""a" true"#,
+            r#"{"a"}"#,
+            r#"{"a" yes}"#,
+            r#"{"a" truer}"#,
+            r#"{"a true}"#,
+            r#"{a true}"#,
+        ] {
+            assert!(malformed.parse::<Filters>().is_err(), "{malformed:?}");
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn metadata_display_parse() {
         let s = "month:: January";
@@ -212,6 +553,13 @@ mod tests {
         let v1 = r#"month:: true"#.parse::<Metadata>()?;
         let v2 = r#"month:: false"#.parse::<Metadata>()?;
         let v3 = r#"week:: false"#.parse::<Metadata>()?;
+        let c1 = r#"created:: 2024-09-01T10:00:00Z"#.parse::<Metadata>()?;
+        let c2 = r#"created:: 2024-10-01T10:00:00Z"#.parse::<Metadata>()?;
+
+        // create-only keys
+        let mut c3 = c1.clone();
+        c3.update(c2.clone());
+        assert_eq!(c1, c3);
 
         // different keys
         let mut v4 = v3.clone();
@@ -241,4 +589,79 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn list() -> anyhow::Result<()> {
+        let m = "alias:: [[Sep 1, 2024]], [[2024-09-01]]".parse::<Metadata>()?;
+        let Value::List(ref l) = m.value else {
+            panic!("m.value is not a List");
+        };
+        assert_eq!(
+            vec!["Sep 1, 2024", "2024-09-01"],
+            l.names().collect::<Vec<_>>()
+        );
+        assert_eq!("alias:: [[Sep 1, 2024]], [[2024-09-01]]", m.to_string());
+
+        let m = "alias:: foo, bar".parse::<Metadata>()?;
+        let Value::List(ref l) = m.value else {
+            panic!("m.value is not a List");
+        };
+        assert_eq!(vec!["foo", "bar"], l.names().collect::<Vec<_>>());
+
+        let mut m1 = "alias:: foo, bar".parse::<Metadata>()?;
+        m1.update(Metadata::from(List::default().push("baz").push("foo")));
+        assert_eq!("alias:: foo, bar, [[baz]]", m1.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn list_round_trip() -> anyhow::Result<()> {
+        for text in [
+            "tags:: #work, [[Project X]], journal",
+            "tags:: #work,[[Project X]] ,  journal",
+            "tags:: [[Sep 1, 2024]], #[[Project X]], [[journal]]",
+        ] {
+            let m = text.parse::<Metadata>()?;
+            assert_eq!(text, m.to_string());
+
+            // merging what it already refers to changes nothing
+            let mut merged = m.clone();
+            merged.update(text.parse()?);
+            merged.update(List::default().push("journal").into_metadata("tags"));
+            assert_eq!(m, merged, "{text}");
+        }
+
+        let mut m = "tags:: #work, journal".parse::<Metadata>()?;
+        m.update("tags:: [[work]], #week".parse()?);
+        assert_eq!("tags:: #work, journal, #week", m.to_string());
+        m.sort_list();
+        assert_eq!("tags:: journal, #week, #work", m.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn policies() -> anyhow::Result<()> {
+        assert_eq!(Policy::CreateOnly, Policy::of("created"));
+        assert_eq!(Policy::Union, Policy::of("tags"));
+        assert_eq!(Policy::Replace, Policy::of("next"));
+
+        // create-only
+        let mut m = "created:: 2024-09-01".parse::<Metadata>()?;
+        m.update("created:: 2024-10-01".parse()?);
+        assert_eq!("created:: 2024-09-01", m.to_string());
+
+        // replace
+        let mut m = "next:: [[2024/Week 36]]".parse::<Metadata>()?;
+        m.update("next:: [[2024/Week 37]]".parse()?);
+        assert_eq!("next:: [[2024/Week 37]]", m.to_string());
+
+        // union
+        let mut m = "tags:: journal, work".parse::<Metadata>()?;
+        m.update("tags:: [[work]], [[week]]".parse()?);
+        assert_eq!("tags:: journal, work, [[week]]", m.to_string());
+
+        Ok(())
+    }
 }
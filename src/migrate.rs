@@ -0,0 +1,136 @@
+use crate::confirm::pages;
+use crate::page::Page;
+use crate::utils::JournalFormat;
+use crate::{Preparer, Summary};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// A page of --migrate-links: its path, the page before and after the
+/// rewrite, and its renamed links
+type Migration = (PathBuf, Page, Page, Vec<(String, String)>);
+
+impl Preparer {
+    pub(crate) fn migrate_links(
+        &self,
+        from: &JournalFormat,
+        to: &JournalFormat,
+    ) -> Result<Summary> {
+        let mut summary = Summary::default();
+
+        let plan = self.migration_plan(from, to)?;
+        let rewritten = plan
+            .iter()
+            .filter(|(_, before, page, _)| page.render() != before.render())
+            .count();
+        if self.writes() && rewritten > 0 {
+            self.confirm
+                .confirm(&format!("rewrite the links of {}", pages(rewritten)))?;
+        }
+
+        for (path, before, mut page, renamed) in plan {
+            let updated = page.render() != before.render();
+            if updated {
+                log::info!("Migrating links of page {}", path.display());
+                self.write_page(&before, &mut page, &path)?;
+            }
+            for (old, new) in renamed {
+                if self.dry_run {
+                    println!("  [[{old}]] -> [[{new}]]");
+                } else {
+                    log::debug!("Renamed link [[{old}]] to [[{new}]]");
+                }
+            }
+            summary.record(updated);
+        }
+
+        Ok(summary)
+    }
+
+    /// The pages whose generated links --migrate-links rewrites from `from`
+    /// to `to`: their path, their page before and after, and the renamed
+    /// links, the ones written by hand staying as they are
+    pub(crate) fn migration_plan(
+        &self,
+        from: &JournalFormat,
+        to: &JournalFormat,
+    ) -> Result<Vec<Migration>> {
+        let mut plan = Vec::new();
+        for path in self.page_paths() {
+            if !path.exists() {
+                continue;
+            }
+
+            let before = self.pages.get(&path)?;
+            let mut page = before.clone().final_newline(self.final_newline);
+            let renamed = page.rewrite_links(
+                |name| from.parse(name).map(|date| to.name(date)),
+                |name| from.parse(name).is_some() || self.is_generated_name(name),
+            );
+            plan.push((path, before, page, renamed));
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confirm::Confirm;
+    use crate::tests::{graph, preparer};
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn migrate_links_dry_run() -> Result<()> {
+        let temp_dir = graph()?;
+        let week = temp_dir.child("pages/2024___Week 36.md");
+        let content = "-\n- {{embed [[2024-09-02]]}}\n- Met on [[2024-09-02]]\n";
+        week.write_str(content)?;
+
+        let args = [
+            "--from",
+            "2024-09-02",
+            "--to",
+            "2024-09-02",
+            "migrate-links",
+            "--from-format",
+            "%Y-%m-%d",
+            "--to-format",
+            "%d/%m/%Y",
+        ];
+        let dry_run = preparer(&temp_dir, &[&["--dry-run"], &args[..]].concat())?;
+        let plan = dry_run.migration_plan(&"%Y-%m-%d".parse()?, &"%d/%m/%Y".parse()?)?;
+        // only the embed, not the link written by hand
+        let renamed = plan
+            .iter()
+            .flat_map(|(_, _, _, renamed)| renamed.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("2024-09-02".to_owned(), "02/09/2024".to_owned())],
+            renamed
+        );
+        let (path, _, page, _) = &plan[0];
+        assert_eq!(week.path(), path);
+        assert_eq!(
+            "-\n- {{embed [[02/09/2024]]}}\n- Met on [[2024-09-02]]\n",
+            page.render()
+        );
+        let summary = dry_run.run()?;
+        assert_eq!(summary.updated, 1);
+        week.assert(content);
+
+        // only once confirmed
+        let mut migration = preparer(&temp_dir, &args)?;
+        migration.confirm = Confirm::Refuse;
+        let error = migration.run().unwrap_err();
+        assert!(
+            error.to_string().contains("rewrite the links of 1 page,"),
+            "{error}"
+        );
+        week.assert(content);
+        migration.confirm = Confirm::Yes;
+        migration.run()?;
+        week.assert("-\n- {{embed [[02/09/2024]]}}\n- Met on [[2024-09-02]]\n");
+
+        Ok(())
+    }
+}
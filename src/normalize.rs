@@ -0,0 +1,81 @@
+use crate::{Preparer, Summary};
+use anyhow::Result;
+
+impl Preparer {
+    pub(crate) fn normalize(&self) -> Result<Summary> {
+        let mut summary = Summary::default();
+
+        for path in self.page_paths() {
+            if !path.exists() {
+                continue;
+            }
+
+            let before = self.pages.get(&path)?;
+            let mut page = before
+                .clone()
+                .block_spacing(self.block_spacing.or(Some(0)))
+                .final_newline(self.final_newline)
+                .leading_block(Some(self.leading_block));
+            page.normalize();
+
+            let updated = page.is_modified();
+            if updated {
+                log::info!("Normalizing page {}", path.display());
+                self.write_page(&before, &mut page, &path)?;
+            }
+            summary.record(updated);
+        }
+
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{graph, preparer};
+    use assert_fs::prelude::*;
+    use indoc::indoc;
+
+    #[test]
+    fn normalize() -> Result<()> {
+        let temp_dir = graph()?;
+        let day = temp_dir.child("journals/2024_09_02.md");
+        day.write_str(indoc! {r#"
+            week:: [[2024/Week 36]]
+            day:: Monday
+            filters:: {"2024/Week 36" false}
+
+            - Meeting notes
+
+            -
+            - DONE Review
+              :LOGBOOK:
+              :END:
+              - with the team
+
+            -
+        "#})?;
+        let args = ["--from", "2024-09-02", "--to", "2024-09-02", "normalize"];
+
+        assert_eq!(1, preparer(&temp_dir, &args)?.run()?.updated);
+        day.assert(indoc! {r#"
+            day:: Monday
+            filters:: {"2024/Week 36" false}
+            week:: [[2024/Week 36]]
+
+            -
+            - Meeting notes
+            - DONE Review
+              :LOGBOOK:
+              :END:
+              - with the team
+        "#});
+        // nothing generated was added, e.g. the week page
+        assert!(!temp_dir.child("pages/2024___Week 36.md").exists());
+
+        assert_eq!(0, preparer(&temp_dir, &args)?.run()?.updated);
+
+        Ok(())
+    }
+}
@@ -1,40 +1,816 @@
-use chrono::NaiveDate;
-use clap::{Parser, ValueEnum};
+use crate::date_utils::{Month, NameStyle, WeekStart, WeekYear};
+use crate::page::{Bullet, Emptied, Indent};
+use crate::recurrence::Recurrence;
+use crate::title::TitleFormat;
+use crate::utils::{FromJournalName, JournalFormat, NameFilter};
+use chrono::{Datelike, Days, IsoWeek, Months, NaiveDate, Weekday};
+use chrono_tz::Tz;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
 use std::path::PathBuf;
 
+/// Extensions of the markdown files logseq reads, the only syntax written
+const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
 #[derive(Default, Clone, Debug, Parser)]
-#[command(version, infer_subcommands = true)]
+#[command(version, infer_subcommands = true, args_override_self = true)]
 pub struct Cli {
     #[clap(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 
+    /// What to do with the range, preparing it by default
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Read arguments from FILE, one per line, before the ones of the
+    /// command line which override them. Lines starting with # are ignored
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Also read the arguments of the `[NAME]` section of the --config file,
+    /// after the ones before the first section
+    #[arg(long, value_name = "NAME", requires = "config")]
+    pub profile: Option<String>,
+
+    /// Print the names of the profiles of the --config file, instead of
+    /// preparing the range
+    #[arg(long, requires = "config")]
+    pub list_profiles: bool,
+
+    /// Profiles of the --config file, in order
+    #[arg(skip)]
+    pub profiles: Vec<String>,
+
+    /// The effective options, after merging the --config file, the command
+    /// line and the defaults, as the lines of a --config file
+    #[arg(skip)]
+    pub effective: Vec<String>,
+
+    /// Print the effective configuration, instead of preparing the range
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "plan_graph", "check"])]
+    pub show_config: bool,
+
+    /// Print how the page at FILE is parsed, its metadata and blocks,
+    /// instead of preparing the range
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["migrate_links", "prune", "plan_graph", "check", "show_config"])]
+    pub inspect: Option<PathBuf>,
+
     /// Path to logseq graph
-    #[arg(short, long)]
+    #[arg(short, long, env = "LOGSEQ_GRAPH")]
     pub path: PathBuf,
 
+    /// Fail when the graph's logseq/config.edn can't be read, instead of
+    /// ignoring it
+    #[arg(long)]
+    pub strict_config: bool,
+
+    /// Prepare the day pages even when the graph's logseq/config.edn
+    /// disables the journals
+    #[arg(long)]
+    pub force: bool,
+
+    /// Write journals and pages together in --path, as graphs without
+    /// journals and pages directories. Detected when --path has neither
+    /// but has pages
+    #[arg(long)]
+    pub flat: bool,
+
     /// Only prepare journal starting from given date
     #[arg(long, value_name = "DATE")]
     pub from: Option<NaiveDate>,
 
-    /// Only prepare journal up to given date
+    /// Only prepare journal up to given date, included: --from X --to X
+    /// prepares the day X
     #[arg(long, value_name = "DATE")]
     pub to: Option<NaiveDate>,
 
+    /// How far from --from the journal is prepared when --to isn't given
+    #[arg(long, value_enum, default_value_t)]
+    pub horizon: Horizon,
+
+    /// Only prepare journal starting from the Monday of given ISO week,
+    /// e.g. 2024-W36
+    #[arg(long, value_name = "WEEK", conflicts_with_all = ["from", "to"], value_parser = parse_iso_week)]
+    pub from_week: Option<IsoWeek>,
+
+    /// Only prepare journal up to the Sunday of given ISO week, included
+    #[arg(long, value_name = "WEEK", conflicts_with_all = ["from", "to"], value_parser = parse_iso_week)]
+    pub to_week: Option<IsoWeek>,
+
+    /// Only prepare the whole months given by name, e.g.
+    /// 2024/September,2024/November, instead of a range of days
+    #[arg(long, value_name = "MONTHS", value_delimiter = ',', value_parser = parse_month, conflicts_with_all = ["from", "to", "from_week", "to_week", "rest_of_month", "rest_of_year"])]
+    pub months: Vec<Month>,
+
+    /// Prepare from today to the end of the month, instead of --from and --to
+    #[arg(long, conflicts_with = "rest_of_year")]
+    pub rest_of_month: bool,
+
+    /// Prepare from today to the end of the year, instead of --from and --to
+    #[arg(long)]
+    pub rest_of_year: bool,
+
+    /// Time zone resolving today's date, the default --from, e.g.
+    /// Europe/Paris. Defaults to UTC
+    #[arg(long, value_name = "TZ")]
+    pub timezone: Option<Tz>,
+
+    /// First day of the weeks, by default the :start-of-week of the graph's
+    /// logseq/config.edn, or the one of the locale set in LC_ALL, LC_TIME or
+    /// LANG, or Monday
+    #[arg(long, value_enum)]
+    pub week_start: Option<WeekStart>,
+
+    /// Year in the week page names, which differ around January 1st
+    #[arg(long, value_enum, default_value_t)]
+    pub week_year: WeekYear,
+
+    /// Month and week segments of the page names, `2024/September` and
+    /// `2024/Week 36`, or `2024/09` and `2024/W36`
+    #[arg(long, value_enum, default_value_t)]
+    pub name_style: NameStyle,
+
     /// Configure day pages header
-    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [DayOption::Day, DayOption::Week])]
+    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [DayOption::Day, DayOption::Week], overrides_with = "day")]
     pub day: Vec<DayOption>,
 
+    /// Capitalization of the day of week in the day pages `day::`
+    #[arg(long, value_enum, default_value_t)]
+    pub day_case: DayCase,
+
     /// Configure week pages header
-    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [WeekOption::Nav, WeekOption::Month])]
+    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [WeekOption::Nav, WeekOption::Month], overrides_with = "week")]
     pub week: Vec<WeekOption>,
 
     /// Configure month pages header
-    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [MonthOption::Nav])]
+    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [MonthOption::Nav], overrides_with = "month")]
     pub month: Vec<MonthOption>,
 
+    /// Links from the pages to the periods holding them
+    #[arg(long, value_enum, default_value_t)]
+    pub hierarchy_style: HierarchyStyle,
+
+    /// Order of the day embeds on the week pages, from the first day of the
+    /// week or, with desc, from the last one
+    #[arg(long, value_enum, default_value_t)]
+    pub day_order: DayOrder,
+
+    /// Embed at most N days on each week, sprint and month page, linking to
+    /// the other ones instead. Unlimited by default
+    #[arg(long, value_name = "N")]
+    pub max_embeds: Option<usize>,
+
+    /// Only embed the days whose page exists on each week, sprint and month
+    /// page, linking to the other ones instead. Their links become embeds
+    /// once they exist
+    #[arg(long)]
+    pub embed_existing_only: bool,
+
+    /// Nest the day embeds of the week pages under a caption with the day
+    /// of week and a link to the day, e.g. `**Monday** [[2024-09-02]]`
+    #[arg(long)]
+    pub day_captions: bool,
+
+    /// Layout of the day embeds on the month pages
+    #[arg(long, value_enum, default_value_t)]
+    pub month_layout: MonthLayout,
+
+    /// Start the month pages with a block linking to each of their weeks
+    #[arg(long)]
+    pub month_week_index: bool,
+
+    /// Configure quarter pages header
+    #[arg(long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [QuarterOption::Nav], overrides_with = "quarter")]
+    pub quarter: Vec<QuarterOption>,
+
     /// Configure year pages header
-    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [YearOption::Nav])]
+    #[arg(short, long, num_args = 0.., value_enum, value_delimiter = ',', default_values_t = [YearOption::Nav], overrides_with = "year")]
     pub year: Vec<YearOption>,
+
+    /// Periods linked from the year pages, quarters linking in turn to the months
+    #[arg(long, value_enum, default_value_t)]
+    pub year_children: YearChildren,
+
+    /// Generate a page per sprint of DAYS days, e.g. 14, embedding its days
+    #[arg(long, value_name = "DAYS", requires = "sprint_anchor", value_parser = clap::value_parser!(u32).range(1..))]
+    pub sprint_length: Option<u32>,
+
+    /// First day of the first sprint, the others following one another from
+    /// there
+    #[arg(long, value_name = "DATE", requires = "sprint_length")]
+    pub sprint_anchor: Option<NaiveDate>,
+
+    /// Template appended after the embeds of week and month pages
+    #[arg(long, value_name = "FILE")]
+    pub overview_footer: Option<PathBuf>,
+
+    /// Template of a companion page of each month page, e.g. `2024/September
+    /// Notes`, for the reflections on the month, linked from its `notes::`.
+    /// Only added when creating the page, which never gets embeds
+    #[arg(long, value_name = "FILE")]
+    pub month_notes: Option<PathBuf>,
+
+    /// Template added to the page of the --from day, if it does not exist yet
+    #[arg(long, value_name = "FILE")]
+    pub bootstrap_block: Option<PathBuf>,
+
+    /// Template added to the day pages of a day of week, e.g. mon=plan.md,
+    /// if they do not exist yet. Repeatable
+    #[arg(long, value_name = "DAY=FILE", value_parser = parse_weekday_template)]
+    pub weekday_template: Vec<(Weekday, PathBuf)>,
+
+    /// Task blocks added to the overview pages of a kind, e.g.
+    /// month=review.md, with the placeholders of the templates. Once
+    /// there, a task marked DONE isn't added again. Repeatable
+    #[arg(long, value_name = "KIND=FILE", value_parser = parse_seed_todos, requires = "mark_generated")]
+    pub seed_todos: Vec<(Period, PathBuf)>,
+
+    /// Add a task to plan the period to the overview pages of these kinds,
+    /// e.g. month,week, scheduled on their first day. Requires
+    /// --mark-generated, whose marker tells the task once marked DONE, so
+    /// that it isn't added again
+    #[arg(
+        long,
+        value_name = "KINDS",
+        value_enum,
+        value_delimiter = ',',
+        requires = "mark_generated"
+    )]
+    pub schedule_blocks: Vec<Period>,
+
+    /// Recurring event listed in the events:: of the day pages it occurs
+    /// on, e.g. "Review=last friday" or "Sync=every other monday".
+    /// Repeatable
+    #[arg(long, value_name = "NAME=RULE")]
+    pub recurrence: Vec<Recurrence>,
+
+    /// Indentation of the generated nested blocks: tab, 2 or 4 spaces.
+    /// Existing pages keep their own indentation
+    #[arg(long, value_name = "INDENT")]
+    pub indent: Option<Indent>,
+
+    /// Bullet of the generated blocks: - or *. Existing pages keep their
+    /// own bullet
+    #[arg(long, value_name = "CHAR")]
+    pub bullet: Option<Bullet>,
+
+    /// Add `days-left-in-month::` and `days-left-in-year::` to the day
+    /// pages, counting the days after them
+    #[arg(long)]
+    pub countdown: bool,
+
+    /// Maintain a `Today` page embedding the day page of today, replacing
+    /// the embed of the previous run
+    #[arg(long)]
+    pub today_page: bool,
+
+    /// Keep the metadata KEY of the existing pages as it is, instead of
+    /// updating it, e.g. filters after editing them by hand. Repeatable
+    #[arg(long, value_name = "KEY")]
+    pub preserve_meta: Vec<String>,
+
+    /// Sort the items of the list metadata, like alias:: and tags::,
+    /// instead of keeping the order they were added in
+    #[arg(long)]
+    pub sort_lists: bool,
+
+    /// Leave the existing blocks byte for byte as they are, only appending
+    /// the new generated blocks at the end of the pages
+    #[arg(long)]
+    pub append_only: bool,
+
+    /// Also leave the metadata of the existing pages as it is
+    #[arg(long, requires = "append_only")]
+    pub freeze_metadata: bool,
+
+    /// Skip writing the week, sprint, month, quarter and year pages that
+    /// would have no block, only their metadata
+    #[arg(long)]
+    pub compact: bool,
+
+    /// Skip writing the week pages starting after today, in --timezone,
+    /// unless one of their day pages has something written by hand
+    #[arg(long)]
+    pub skip_future_weeks: bool,
+
+    /// Copy the TODO and DOING tasks of the previous day to the day pages
+    /// this run creates
+    #[arg(long)]
+    pub carry_tasks: bool,
+
+    /// End the day pages this run creates with an embed of their week
+    #[arg(long)]
+    pub day_week_footer: bool,
+
+    /// Start the day pages this run creates with a heading block of their
+    /// date, e.g. `- # 2024-09-01`, in --title-format or else like their name
+    #[arg(long)]
+    pub title_heading: bool,
+
+    /// Link the day pages this run creates to the same day of the N
+    /// previous years, up to 100, when their pages exist
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=100))]
+    pub on_this_day: Option<u32>,
+
+    /// Add a `created::` timestamp, in --timezone, to the pages this run
+    /// creates. Existing pages keep theirs
+    #[arg(long)]
+    pub stamp_created: bool,
+
+    /// Add a `prepared-by::` property with the version of this tool to the
+    /// pages this run creates. Existing pages keep theirs
+    #[arg(long)]
+    pub stamp_version: bool,
+
+    /// Update the `prepared-by::` property of the existing pages too
+    #[arg(long, requires = "stamp_version")]
+    pub restamp: bool,
+
+    /// What the week and month pages embed for each day: the day page, or
+    /// its first block when it has an `id::`
+    #[arg(long, value_enum, default_value_t)]
+    pub embed_target: EmbedTarget,
+
+    /// Fold the generated embeds with a `collapsed:: true` block property.
+    /// Existing blocks keep their folding state
+    #[arg(long)]
+    pub collapse_embeds: bool,
+
+    /// Blank lines between the top-level blocks: 0 or 1. Existing pages
+    /// keep their own spacing unless set
+    #[arg(long, value_name = "LINES", value_parser = clap::value_parser!(u8).range(0..=1))]
+    pub block_spacing: Option<u8>,
+
+    /// End every written page with exactly one newline, or none with
+    /// `false`, instead of a blank line after the metadata of the pages
+    /// without blocks
+    #[arg(long, value_name = "BOOL")]
+    pub final_newline: Option<bool>,
+
+    /// Whether to write an empty block at the top of the markdown pages,
+    /// which the older logseq versions need to read their properties
+    #[arg(long, value_name = "WHEN", value_enum, default_value_t)]
+    pub leading_block: LeadingBlock,
+
+    /// Times to try writing a page again when the file is busy or locked,
+    /// e.g. by the daemon of a synced folder, up to 10, waiting 50ms then
+    /// twice as long each time, at most a second
+    #[arg(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(0..=10))]
+    pub io_retries: u32,
+
+    /// Mark generated blocks with a `generated:: true` block property
+    #[arg(long)]
+    pub mark_generated: bool,
+
+    /// What to do with the generated blocks emptied by hand, recognized by
+    /// the marker of --mark-generated
+    #[arg(long, value_name = "POLICY", value_enum, default_value_t)]
+    pub emptied_blocks: Emptied,
+
+    /// Add a `title::` to day pages, formatted with chrono's syntax and the
+    /// {quarter}, {isoweek}, {isoyear}, {ordinal} and {dayth} tokens
+    #[arg(long, value_name = "FORMAT")]
+    pub title_format: Option<TitleFormat>,
+
+    /// Add an `alias::` to day pages with their name in this format, with
+    /// the tokens of --title-format. Can be repeated since formats may
+    /// contain commas
+    #[arg(long, value_name = "FORMAT")]
+    pub aliases: Vec<TitleFormat>,
+
+    /// Add a `title::` to the week, month and quarter pages, their name with
+    /// SEP instead of the namespace `/`, e.g. `2024 · September`, and link
+    /// to them by that title. Their files stay in the namespace
+    #[arg(long, value_name = "SEP")]
+    pub title_separator: Option<String>,
+
+    /// Don't prepare the dates listed in FILE, one `YYYY-MM-DD` or
+    /// `YYYY-MM-DD..YYYY-MM-DD` range per line, e.g. holidays
+    #[arg(long, value_name = "FILE")]
+    pub skip_dates_file: Option<PathBuf>,
+
+    /// Experimental: only prepare the days with a commit in the git
+    /// repository at REPO, by author date, from the first one to the last
+    /// one unless --from and --to are given. Needs the git command
+    #[arg(long, value_name = "REPO", conflicts_with_all = ["months", "rest_of_month", "rest_of_year"])]
+    pub from_git: Option<PathBuf>,
+
+    /// Only touch the pages whose name matches this pattern, where `*`
+    /// matches anything, e.g. "*/Week *". Can be repeated
+    #[arg(long, value_name = "PATTERN")]
+    pub name_filter: Vec<NameFilter>,
+
+    /// Extension of the day page files, among the markdown ones: the pages
+    /// are written in markdown
+    #[arg(long, value_name = "EXT", default_value = "md", value_parser = MARKDOWN_EXTENSIONS)]
+    pub journal_extension: String,
+
+    /// Also write the content of every written page next to it, e.g.
+    /// `2024_09_01.json`, for the tools that don't read logseq's markdown
+    #[arg(long, value_name = "FORMAT")]
+    pub sidecar: Option<Sidecar>,
+
+    /// Write the day pages into a directory per year, e.g.
+    /// `journals/2024/`, or per month, e.g. `journals/2024/09/`. Their names
+    /// and links don't change
+    #[arg(long, value_name = "PARTITION", conflicts_with = "flat")]
+    pub journal_partition: Option<Partition>,
+
+    /// Extension of the other page files, among the markdown ones
+    #[arg(long, value_name = "EXT", default_value = "md", value_parser = MARKDOWN_EXTENSIONS)]
+    pub page_extension: String,
+
+    /// Format of the day page names, as set in logseq's :journal/page-title-format
+    #[arg(long, value_name = "FORMAT", default_value = "%Y-%m-%d")]
+    pub journal_format: JournalFormat,
+
+    /// Rewrite the generated links to day pages of the existing pages in the
+    /// range from --from-format to --to-format, instead of preparing them
+    #[arg(long, requires_all = ["from_format", "to_format"])]
+    pub migrate_links: bool,
+
+    /// Delete the prepared pages outside of the range that only hold
+    /// generated content, instead of preparing the range
+    #[arg(long, conflicts_with = "migrate_links")]
+    pub prune: bool,
+
+    /// Print the pages of the range and their links as a graphviz DOT
+    /// graph, instead of preparing them
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "check"])]
+    pub plan_graph: bool,
+
+    /// Rewrite the existing pages of the range in the canonical format,
+    /// sorting their metadata and removing the stray empty blocks, without
+    /// adding anything, instead of preparing them
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "plan_graph", "check"])]
+    pub normalize: bool,
+
+    /// Validate the config, the formats, the templates and the existing
+    /// pages of the range, failing on the first invalid one, without
+    /// preparing anything
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "plan_graph", "check", "normalize"])]
+    pub preflight: bool,
+
+    /// Print the existing and missing pages of the range, the blocks per
+    /// day page and the tasks, without preparing anything
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "plan_graph", "check", "normalize", "preflight"])]
+    pub stats: bool,
+
+    /// Only refresh the `next::`, `prev::`, `week::`, `month::`, `year::`,
+    /// `sprint::`, `parent::` and `notes::` links of the existing pages of
+    /// the range, leaving their other metadata and their blocks as they are
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "plan_graph", "normalize", "preflight"])]
+    pub links_only: bool,
+
+    /// Merge the pages of the range of the graph at GRAPH into the ones of
+    /// this graph, found by the same file names, their metadata kept on
+    /// conflict
+    #[arg(long, value_name = "GRAPH", conflicts_with_all = ["migrate_links", "prune", "plan_graph", "normalize", "preflight", "stats", "links_only"])]
+    pub merge_from: Option<PathBuf>,
+
+    /// Continue an interrupted run of the same range from its last fully
+    /// written day, once its pages are verified
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "normalize", "check", "dry_run"])]
+    pub resume: bool,
+
+    /// Stop after the day where N pages have been written, printing the
+    /// --from to continue with
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Never modify an existing page: fail when one of the range is not
+    /// up to date, instead of merging the prepared content into it. Only
+    /// when preparing pages, the commands rewriting the existing ones
+    /// refuse it
+    #[arg(long, conflicts_with_all = ["migrate_links", "prune", "normalize", "merge_from"])]
+    pub no_clobber: bool,
+
+    /// With --no-clobber, skip the existing pages with a warning instead of
+    /// failing
+    #[arg(long, requires = "no_clobber")]
+    pub keep_going: bool,
+
+    /// Delete or rewrite the pages with --prune and --migrate-links without
+    /// asking first, as required outside of a terminal
+    #[arg(long)]
+    pub yes: bool,
+
+    /// Only report what would be written or deleted
+    #[arg(long, group = "preview")]
+    pub dry_run: bool,
+
+    /// Fail if any page in the range is out of date, without writing
+    #[arg(long, group = "preview", conflicts_with_all = ["migrate_links", "prune"])]
+    pub check: bool,
+
+    /// With --dry-run or --check, also show the changes of each page
+    #[arg(long, requires = "preview")]
+    pub diff: bool,
+
+    /// Previous format of the day page names, for --migrate-links
+    #[arg(long, value_name = "FORMAT")]
+    pub from_format: Option<JournalFormat>,
+
+    /// New format of the day page names, for --migrate-links
+    #[arg(long, value_name = "FORMAT")]
+    pub to_format: Option<JournalFormat>,
+}
+
+/// Value of the option `flag` in `args`, as `flag VALUE` or `flag=VALUE`
+fn value_of(args: &[OsString], flag: &str) -> Option<OsString> {
+    args.iter()
+        .enumerate()
+        .find_map(|(index, arg)| match arg.to_str()?.strip_prefix(flag) {
+            Some("") => args.get(index + 1).cloned(),
+            Some(value) => value.strip_prefix('=').map(OsString::from),
+            None => None,
+        })
+}
+
+/// Arguments of a --config file, one per line: the ones before the first
+/// section, and the ones of each `[NAME]` section
+fn sections(text: &str) -> (Vec<String>, Vec<(String, Vec<String>)>) {
+    let mut defaults = Vec::new();
+    let mut sections = Vec::<(String, Vec<String>)>::new();
+    for line in text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    {
+        let section = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'));
+        match (section, sections.last_mut()) {
+            (Some(name), _) => sections.push((name.trim().to_owned(), Vec::new())),
+            (None, Some((_, lines))) => lines.push(line.to_owned()),
+            (None, None) => defaults.push(line.to_owned()),
+        }
+    }
+    (defaults, sections)
+}
+
+/// The options of `matches`, as the lines of a --config file, but the ones
+/// reading or showing the configuration
+fn effective(command: &clap::Command, matches: &clap::ArgMatches) -> Vec<String> {
+    let mut lines = Vec::new();
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        let Some(flag) = arg.get_long().map(|long| format!("--{long}")) else {
+            continue;
+        };
+        if ["config", "profile", "list_profiles", "show_config"].contains(&id) {
+            continue;
+        }
+        match arg.get_action() {
+            ArgAction::SetTrue if matches.get_flag(id) => lines.push(flag),
+            ArgAction::Count => {
+                lines.extend(std::iter::repeat_n(flag, matches.get_count(id).into()))
+            }
+            ArgAction::Set | ArgAction::Append => {
+                let delimiter = arg.get_value_delimiter().unwrap_or(',').to_string();
+                for values in matches.get_raw_occurrences(id).into_iter().flatten() {
+                    lines.push(flag.clone());
+                    let values = values
+                        .map(|value| value.to_string_lossy())
+                        .collect::<Vec<_>>();
+                    if !values.is_empty() {
+                        lines.push(values.join(&delimiter));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+impl Cli {
+    /// Parse `args`, preceded by the arguments of the --config file if any
+    pub fn parse_with_config<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut args = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+
+        let mut profiles = Vec::new();
+        if let Some(config) = value_of(&args, "--config") {
+            let text = std::fs::read_to_string(&config).map_err(|e| {
+                clap::Error::raw(
+                    clap::error::ErrorKind::Io,
+                    format!("Cannot read config {:?}: {}\n", config, e),
+                )
+            })?;
+            let (defaults, sections) = sections(&text);
+            profiles = sections.iter().map(|(name, _)| name.to_owned()).collect();
+
+            let mut lines = defaults;
+            if let Some(profile) = value_of(&args, "--profile") {
+                let profile = profile.to_string_lossy();
+                let Some((_, section)) = sections.into_iter().find(|(name, _)| *name == profile)
+                else {
+                    return Err(clap::Error::raw(
+                        clap::error::ErrorKind::InvalidValue,
+                        format!(
+                            "Unknown profile {profile:?} in {config:?}, expected one of: {}\n",
+                            profiles.join(", ")
+                        ),
+                    ));
+                };
+                lines.extend(section);
+            }
+            args.splice(1..1, lines.into_iter().map(OsString::from));
+        }
+
+        let mut command = Self::command();
+        let matches = command.try_get_matches_from_mut(args)?;
+        let cli = Self::from_arg_matches(&matches)
+            .map_err(|e| e.format(&mut command))?
+            .with_command()?;
+        let effective = effective(&command, &matches);
+        Ok(Self {
+            profiles,
+            effective,
+            ..cli
+        })
+    }
+
+    /// Set the flags equivalent to the subcommand, which conflicts with the
+    /// ones of the other subcommands
+    fn with_command(mut self) -> Result<Self, clap::Error> {
+        let Some(command) = &self.command else {
+            return Ok(self);
+        };
+
+        // the flags only changing the pages being prepared
+        let prepare = [
+            ("--carry-tasks", self.carry_tasks),
+            ("--day-captions", self.day_captions),
+            ("--today-page", self.today_page),
+            ("--seed-todos", !self.seed_todos.is_empty()),
+            ("--bootstrap-block", self.bootstrap_block.is_some()),
+            ("--weekday-template", !self.weekday_template.is_empty()),
+            ("--on-this-day", self.on_this_day.is_some()),
+            ("--countdown", self.countdown),
+            ("--title-heading", self.title_heading),
+            ("--day-week-footer", self.day_week_footer),
+            ("--collapse-embeds", self.collapse_embeds),
+            ("--stamp-created", self.stamp_created),
+            ("--stamp-version", self.stamp_version),
+            ("--append-only", self.append_only),
+        ];
+        let conflicts = match command {
+            Command::Prepare => vec![],
+            Command::Prune
+            | Command::MigrateLinks { .. }
+            | Command::Normalize
+            | Command::MergeGraphs { .. } => {
+                let mut conflicts =
+                    vec![("--resume", self.resume), ("--no-clobber", self.no_clobber)];
+                conflicts.extend(prepare);
+                conflicts
+            }
+            Command::Check => vec![
+                ("--resume", self.resume),
+                ("--no-clobber", self.no_clobber),
+                ("--dry-run", self.dry_run),
+            ],
+            Command::PlanGraph => vec![],
+        };
+        let modes = [
+            ("--migrate-links", self.migrate_links),
+            ("--prune", self.prune),
+            ("--check", self.check),
+            ("--plan-graph", self.plan_graph),
+            ("--normalize", self.normalize),
+            ("--preflight", self.preflight),
+            ("--stats", self.stats),
+            ("--links-only", self.links_only),
+            ("--merge-from", self.merge_from.is_some()),
+            ("--show-config", self.show_config),
+            ("--inspect", self.inspect.is_some()),
+        ];
+        if let Some((flag, _)) = modes.into_iter().chain(conflicts).find(|(_, set)| *set) {
+            return Err(Self::command().error(
+                clap::error::ErrorKind::ArgumentConflict,
+                format!(
+                    "{flag} cannot be used with the {} subcommand",
+                    command.name()
+                ),
+            ));
+        }
+
+        match command.clone() {
+            Command::Prepare => {}
+            Command::Prune => self.prune = true,
+            Command::MigrateLinks {
+                from_format,
+                to_format,
+            } => {
+                self.migrate_links = true;
+                self.from_format = Some(from_format);
+                self.to_format = Some(to_format);
+            }
+            Command::Check => self.check = true,
+            Command::PlanGraph => self.plan_graph = true,
+            Command::Normalize => self.normalize = true,
+            Command::MergeGraphs { source } => self.merge_from = Some(source),
+        }
+        Ok(self)
+    }
+}
+
+// The subcommands are the same as their flag, which they can't be combined
+// with. A doc comment would become the about of the whole command
+#[derive(Clone, Debug, PartialEq, Subcommand)]
+pub enum Command {
+    /// Prepare the pages of the range, the default
+    Prepare,
+    /// Delete the prepared pages outside of the range, like --prune
+    Prune,
+    /// Rewrite the links to day pages, like --migrate-links
+    MigrateLinks {
+        /// Previous format of the day page names
+        #[arg(long, value_name = "FORMAT")]
+        from_format: JournalFormat,
+
+        /// New format of the day page names
+        #[arg(long, value_name = "FORMAT")]
+        to_format: JournalFormat,
+    },
+    /// List the pages which are not up to date, like --check
+    Check,
+    /// Print the pages of the range as a graphviz DOT graph, like
+    /// --plan-graph
+    PlanGraph,
+    /// Rewrite the existing pages in the canonical format, like --normalize
+    Normalize,
+    /// Merge the pages of the range of another graph into this one, like
+    /// --merge-from
+    MergeGraphs {
+        /// Graph to take the pages from
+        #[arg(long, value_name = "GRAPH")]
+        source: PathBuf,
+    },
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Prepare => "prepare",
+            Self::Prune => "prune",
+            Self::MigrateLinks { .. } => "migrate-links",
+            Self::Check => "check",
+            Self::PlanGraph => "plan-graph",
+            Self::Normalize => "normalize",
+            Self::MergeGraphs { .. } => "merge-graphs",
+        }
+    }
+}
+
+/// Month page name, e.g. `2024/September` or `2024/09`
+fn parse_month(s: &str) -> Result<Month, String> {
+    Month::from_journal_name(s.trim())
+        .ok_or_else(|| format!("expected a month page name, e.g. 2024/September, found {s:?}"))
+}
+
+/// ISO week written `2024-W36`, the week-year and the week number
+fn parse_iso_week(s: &str) -> Result<IsoWeek, String> {
+    let (year, week) = s
+        .split_once("-W")
+        .ok_or_else(|| format!("expected YEAR-WNUMBER, e.g. 2024-W36, found {s:?}"))?;
+    let year = year
+        .parse()
+        .map_err(|e| format!("invalid year {year:?}: {e}"))?;
+    let week = week
+        .parse()
+        .map_err(|e| format!("invalid week {week:?}: {e}"))?;
+
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+        .map(|monday| monday.iso_week())
+        .ok_or_else(|| format!("{year} has no week {week}"))
+}
+
+/// Day of week and template file, written `mon=plan.md`
+fn parse_weekday_template(s: &str) -> Result<(Weekday, PathBuf), String> {
+    let (day, path) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected DAY=FILE, e.g. mon=plan.md, found {s:?}"))?;
+    let day = day
+        .parse()
+        .map_err(|_| format!("invalid day of week {day:?}, expected e.g. mon or monday"))?;
+    Ok((day, PathBuf::from(path)))
+}
+
+/// Kind of overview page and template file, written `month=review.md`
+fn parse_seed_todos(s: &str) -> Result<(Period, PathBuf), String> {
+    let (kind, path) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected KIND=FILE, e.g. month=review.md, found {s:?}"))?;
+    let kind = Period::from_str(kind, true).map_err(|_| {
+        format!("invalid page kind {kind:?}, expected week, month, quarter or year")
+    })?;
+    Ok((kind, PathBuf::from(path)))
 }
 
 #[derive(Clone, Debug, ValueEnum)]
@@ -45,14 +821,17 @@ pub enum DayOption {
     Week,
     /// Display link to month
     Month,
+    /// Display link to sprint, with --sprint-length
+    Sprint,
 }
 
-#[derive(derive_more::Display)]
-#[display("Day options: {{ day of week: {day}, week: {week}, month: {month} }}")]
+#[derive(Clone, Debug, derive_more::Display)]
+#[display("Day options: {{ day of week: {day}, week: {week}, month: {month}, sprint: {sprint} }}")]
 pub struct DayOptions {
     pub day: bool,
     pub week: bool,
     pub month: bool,
+    pub sprint: bool,
 }
 
 impl From<Vec<DayOption>> for DayOptions {
@@ -61,6 +840,114 @@ impl From<Vec<DayOption>> for DayOptions {
             day: vec.iter().any(|o| matches!(o, DayOption::Day)),
             week: vec.iter().any(|o| matches!(o, DayOption::Week)),
             month: vec.iter().any(|o| matches!(o, DayOption::Month)),
+            sprint: vec.iter().any(|o| matches!(o, DayOption::Sprint)),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Horizon {
+    /// A week after --from
+    Week,
+    /// A month after --from
+    #[default]
+    Month,
+    /// Three months after --from
+    Quarter,
+    /// A year after --from
+    Year,
+}
+
+impl Horizon {
+    /// The default end of a range starting on `from`
+    pub fn end(&self, from: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Week => from + Days::new(7),
+            Self::Month => from + Months::new(1),
+            Self::Quarter => from + Months::new(3),
+            Self::Year => from + Months::new(12),
+        }
+    }
+}
+
+/// Kind of overview page
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Period {
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum LeadingBlock {
+    /// Always write the empty block
+    #[default]
+    Always,
+    /// Never write it
+    Never,
+    /// Leave it out for the graphs whose logseq/config.edn has a
+    /// `:meta/version` above 1. It is the version of the config format,
+    /// which every current logseq writes as 1, so this behaves like always
+    /// until a later format
+    Auto,
+}
+
+impl LeadingBlock {
+    /// Whether to write the empty block in a graph with the config format
+    /// `version`
+    pub fn writes(&self, version: Option<u32>) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => version.is_none_or(|version| version <= 1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Sidecar {
+    /// The metadata as an object and the blocks as an array of strings
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Partition {
+    /// journals/2024/
+    Year,
+    /// journals/2024/09/
+    Month,
+}
+
+impl Partition {
+    /// Directory of the day page of `date`, in the journals directory
+    pub fn dir(&self, date: NaiveDate) -> PathBuf {
+        let year = PathBuf::from(date.format("%Y").to_string());
+        match self {
+            Self::Year => year,
+            Self::Month => year.join(date.format("%m").to_string()),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum DayCase {
+    /// Monday
+    #[default]
+    Title,
+    /// monday
+    Lower,
+    /// MONDAY
+    Upper,
+}
+
+impl DayCase {
+    /// `day`, in title case, in this case
+    pub fn apply(&self, day: &str) -> String {
+        match self {
+            Self::Title => day.to_owned(),
+            Self::Lower => day.to_lowercase(),
+            Self::Upper => day.to_uppercase(),
         }
     }
 }
@@ -73,7 +960,7 @@ pub enum WeekOption {
     Nav,
 }
 
-#[derive(derive_more::Display)]
+#[derive(Clone, Debug, derive_more::Display)]
 #[display("Week options: {{ navigation links: {nav}, month: {month} }}")]
 pub struct WeekOptions {
     pub nav: bool,
@@ -95,7 +982,7 @@ pub enum MonthOption {
     Nav,
 }
 
-#[derive(derive_more::Display)]
+#[derive(Clone, Debug, derive_more::Display)]
 #[display("Month options: {{ navigation links: {nav} }}")]
 pub struct MonthOptions {
     pub nav: bool,
@@ -109,13 +996,33 @@ impl From<Vec<MonthOption>> for MonthOptions {
     }
 }
 
+#[derive(Clone, Debug, ValueEnum)]
+pub enum QuarterOption {
+    /// Display links to previous and next quarter
+    Nav,
+}
+
+#[derive(Clone, Debug, derive_more::Display)]
+#[display("Quarter options: {{ navigation links: {nav} }}")]
+pub struct QuarterOptions {
+    pub nav: bool,
+}
+
+impl From<Vec<QuarterOption>> for QuarterOptions {
+    fn from(vec: Vec<QuarterOption>) -> Self {
+        Self {
+            nav: vec.iter().any(|o| matches!(o, QuarterOption::Nav)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, ValueEnum)]
 pub enum YearOption {
     /// Display links to previous and next year
     Nav,
 }
 
-#[derive(derive_more::Display)]
+#[derive(Clone, Debug, derive_more::Display)]
 #[display("Year options: {{ navigation links: {nav} }}")]
 pub struct YearOptions {
     pub nav: bool,
@@ -128,3 +1035,314 @@ impl From<Vec<YearOption>> for YearOptions {
         }
     }
 }
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum DayOrder {
+    /// First day of the week first
+    #[default]
+    Asc,
+    /// Most recent day first
+    Desc,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum HierarchyStyle {
+    /// `week::` and `month::` links, as configured by --day and --week
+    #[default]
+    Specific,
+    /// A single `parent::` link to the enclosing period: day to week, week
+    /// to month, month to quarter or year, quarter to year
+    Parent,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum MonthLayout {
+    /// One embed per day
+    #[default]
+    Flat,
+    /// The days embedded under a link to their week
+    ByWeek,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum YearChildren {
+    /// Link to the twelve months
+    #[default]
+    Months,
+    /// Link to the four quarters, and generate their pages
+    Quarters,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum EmbedTarget {
+    /// Embed the day page
+    #[default]
+    Page,
+    /// Embed the first block of the day page, or the page if it has no id
+    Block,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn command() -> anyhow::Result<()> {
+        let parse = |args: &[&str]| {
+            Cli::parse_with_config(
+                ["logseq-journal-prepare", "--path", "graph"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+
+        let cli = parse(&[])?;
+        assert_eq!(None, cli.command);
+        assert!(!cli.prune && !cli.migrate_links && !cli.check && !cli.plan_graph);
+
+        let cli = parse(&["prepare"])?;
+        assert_eq!(Some(Command::Prepare), cli.command);
+        assert!(!cli.prune && !cli.migrate_links && !cli.check && !cli.plan_graph);
+
+        assert!(parse(&["prune"])?.prune);
+        assert!(parse(&["check"])?.check);
+        assert!(parse(&["plan-graph"])?.plan_graph);
+        assert!(parse(&["normalize"])?.normalize);
+        assert!(parse(&["--normalize", "prune"]).is_err());
+        let cli = parse(&["merge-graphs", "--source", "work"])?;
+        assert_eq!(Some(PathBuf::from("work")), cli.merge_from);
+        assert!(parse(&["--merge-from", "work", "normalize"]).is_err());
+
+        let cli = parse(&[
+            "mig",
+            "--from-format",
+            "%Y-%m-%d",
+            "--to-format",
+            "%d/%m/%Y",
+        ])?;
+        assert!(cli.migrate_links);
+        assert_eq!(Some("%d/%m/%Y".parse()?), cli.to_format);
+        assert!(parse(&["migrate-links", "--from-format", "%Y-%m-%d"]).is_err());
+
+        // the flags still work, but not along another subcommand
+        assert!(parse(&["--prune"])?.prune);
+        assert!(parse(&["--prune", "prepare"]).is_err());
+        let error = parse(&["--check", "prune"]).unwrap_err();
+        assert_eq!(clap::error::ErrorKind::ArgumentConflict, error.kind());
+        assert!(parse(&["--resume", "check"]).is_err());
+        let error = parse(&["--carry-tasks", "prune"]).unwrap_err();
+        assert!(error.to_string().contains("--carry-tasks"), "{error}");
+        assert!(parse(&["--day-captions", "normalize"]).is_err());
+        assert!(parse(&["--carry-tasks", "check"])?.carry_tasks);
+        // the pages they rewrite all exist
+        for command in [
+            &["normalize"][..],
+            &["--normalize"],
+            &["--merge-from", "work"],
+        ] {
+            assert!(parse(&[&["--no-clobber"], command].concat()).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bounds() -> anyhow::Result<()> {
+        let parse = |args: &[&str]| {
+            Cli::parse_with_config(
+                ["logseq-journal-prepare", "--path", "graph"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+
+        assert_eq!(10, parse(&["--io-retries", "10"])?.io_retries);
+        assert!(parse(&["--io-retries", "11"]).is_err());
+        assert_eq!(Some(100), parse(&["--on-this-day", "100"])?.on_this_day);
+        assert!(parse(&["--on-this-day", "0"]).is_err());
+        assert!(parse(&["--on-this-day", "101"]).is_err());
+        assert!(parse(&["--on-this-day", "4294967295"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn path_env() -> anyhow::Result<()> {
+        // the only test setting it, the others passing --path
+        std::env::remove_var("LOGSEQ_GRAPH");
+        let error = Cli::parse_with_config(["prepare"]).unwrap_err();
+        assert_eq!(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            error.kind()
+        );
+        assert!(error.to_string().contains("--path"), "{error}");
+
+        let cli = Cli::parse_with_config(["prepare", "--path", "flag"])?;
+        assert_eq!(PathBuf::from("flag"), cli.path);
+
+        std::env::set_var("LOGSEQ_GRAPH", "env");
+        let env = Cli::parse_with_config(["prepare"]);
+        let flag = Cli::parse_with_config(["prepare", "--path", "flag"]);
+        std::env::remove_var("LOGSEQ_GRAPH");
+        assert_eq!(PathBuf::from("env"), env?.path);
+        assert_eq!(PathBuf::from("flag"), flag?.path);
+
+        Ok(())
+    }
+
+    #[test]
+    fn iso_week() {
+        let week = parse_iso_week("2024-W36").unwrap();
+        assert_eq!((2024, 36), (week.year(), week.week()));
+        let week = parse_iso_week("2020-W53").unwrap();
+        assert_eq!((2020, 53), (week.year(), week.week()));
+
+        assert_eq!(
+            "2024 has no week 53",
+            parse_iso_week("2024-W53").unwrap_err()
+        );
+        assert!(parse_iso_week("2024-W00").is_err());
+        assert!(parse_iso_week("2024-36").is_err());
+        assert!(parse_iso_week("2024-Wx").is_err());
+
+        let error =
+            Cli::parse_with_config(["prepare", "--path", "graph", "--from-week", "2024-W54"])
+                .unwrap_err();
+        assert_eq!(clap::error::ErrorKind::ValueValidation, error.kind());
+        assert!(Cli::parse_with_config([
+            "prepare",
+            "--path",
+            "graph",
+            "--from-week",
+            "2024-W36",
+            "--from",
+            "2024-09-01"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn day_case() {
+        assert_eq!("Monday", DayCase::Title.apply("Monday"));
+        assert_eq!("monday", DayCase::Lower.apply("Monday"));
+        assert_eq!("MONDAY", DayCase::Upper.apply("Monday"));
+    }
+
+    #[test]
+    fn config() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let config = temp_dir.child("config");
+        config.write_str("# defaults\n--path\ngraph\n--week-start\nsunday\n--day\nweek\n--journal-format\n%b %-d, %Y\n")?;
+        let config = config.path().to_str().unwrap();
+
+        let cli = Cli::parse_with_config([
+            "prepare",
+            "--config",
+            config,
+            "--week-start",
+            "monday",
+            "--day",
+            "month",
+        ])?;
+        // The command line overrides the file
+        assert_eq!(Some(WeekStart::Monday), cli.week_start);
+        assert!(matches!(cli.day[..], [DayOption::Month]));
+        // The file overrides the defaults
+        assert_eq!(PathBuf::from("graph"), cli.path);
+        assert_eq!("%b %-d, %Y".parse::<JournalFormat>()?, cli.journal_format);
+        // Defaults
+        assert_eq!(None, cli.title_format);
+        assert!(matches!(cli.week[..], [WeekOption::Nav, WeekOption::Month]));
+
+        // as shown by --show-config
+        let lines = [
+            "--path",
+            "graph",
+            "--week-start",
+            "monday",
+            "--day",
+            "month",
+        ];
+        for option in lines.chunks(2) {
+            assert!(
+                cli.effective.windows(2).any(|line| line == option),
+                "{option:?} in {:?}",
+                cli.effective
+            );
+        }
+        assert!(cli
+            .effective
+            .windows(2)
+            .any(|line| line == ["--week", "nav,month"]));
+        assert!(!cli.effective.iter().any(|line| line == "--config"));
+        assert!(!cli.effective.iter().any(|line| line == "--dry-run"));
+
+        let cli = Cli::parse_with_config(["prepare", &format!("--config={config}")])?;
+        assert_eq!(Some(WeekStart::Sunday), cli.week_start);
+        assert!(matches!(cli.day[..], [DayOption::Week]));
+
+        assert!(Cli::parse_with_config(["prepare", "--config", "missing"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn profiles() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let config = temp_dir.child("config");
+        config.write_str(indoc::indoc! {"
+            --week-start
+            sunday
+            --day
+            week
+
+            [work]
+            --path
+            work
+            --day
+            month
+
+            # personal graph
+            [personal]
+            --path
+            personal
+        "})?;
+        let config = config.path().to_str().unwrap();
+
+        // The profile overrides the arguments before the sections, and the
+        // command line the profile
+        let cli = Cli::parse_with_config(["prepare", "--config", config, "--profile", "work"])?;
+        assert_eq!(PathBuf::from("work"), cli.path);
+        assert!(matches!(cli.day[..], [DayOption::Month]));
+        assert_eq!(Some(WeekStart::Sunday), cli.week_start);
+        assert_eq!(vec!["work", "personal"], cli.profiles);
+        let cli = Cli::parse_with_config([
+            "prepare",
+            "--config",
+            config,
+            "--profile=personal",
+            "--path",
+            "other",
+        ])?;
+        assert_eq!(PathBuf::from("other"), cli.path);
+        assert!(matches!(cli.day[..], [DayOption::Week]));
+
+        let error = Cli::parse_with_config(["prepare", "--config", config, "--profile", "home"])
+            .unwrap_err();
+        assert_eq!(clap::error::ErrorKind::InvalidValue, error.kind());
+        assert!(
+            error
+                .to_string()
+                .contains("expected one of: work, personal"),
+            "{error}"
+        );
+        // the sections are only read with --profile
+        assert!(Cli::parse_with_config(["prepare", "--config", config]).is_err());
+        assert!(
+            Cli::parse_with_config(["prepare", "--path", "graph", "--profile", "work"]).is_err()
+        );
+
+        Ok(())
+    }
+}
@@ -1,15 +1,223 @@
-use crate::metadata::Metadata;
-use anyhow::{Context, Result};
+use crate::error::PrepareError;
+use crate::metadata::{json_string, Metadata, Value};
+use crate::retry::retry;
+use crate::utils::Link;
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
 use std::fmt::{Display, Formatter};
 use std::io::Write;
 use std::ops::Add;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-#[derive(Debug)]
+/// Block property carried by the blocks written by this tool
+const GENERATED: &str = "generated:: true";
+
+/// Block property of logseq holding the folding state of a block
+const COLLAPSED: &str = "collapsed::";
+
+/// Markers of the unfinished tasks, the ones of logseq's default TODO/DOING
+/// workflow first, then the LATER/NOW ones
+pub const OPEN: [&str; 4] = ["TODO", "DOING", "LATER", "NOW"];
+
+/// Markers of the finished tasks
+pub const CLOSED: [&str; 2] = ["DONE", "CANCELED"];
+
+/// Lines scheduling a task, under its first one
+const PLANNING: [&str; 2] = ["SCHEDULED:", "DEADLINE:"];
+
+/// Metadata keys holding generated links
+const LINK_KEYS: [&str; 8] = [
+    "next", "prev", "week", "month", "year", "sprint", "parent", "notes",
+];
+
+/// Other metadata keys set by this tool
+const GENERATED_KEYS: [&str; 5] = [
+    "filters",
+    "day",
+    "prepared-by",
+    "days-left-in-month",
+    "days-left-in-year",
+];
+
+/// Metadata keys set by this tool which are also written by hand, only
+/// generated with the value the options give them
+const SHARED_KEYS: [&str; 4] = ["title", "alias", "created", "events"];
+
+lazy_static! {
+    static ref GENERATED_SYNTAX: Regex =
+        Regex::new(r"^- (\{\{embed (\[\[[^\]]+\]\]|\(\([^)]+\)\))\}\}|\[\[[^\]]+\]\])$").unwrap();
+    static ref CAPTION: Regex = Regex::new(r"^- \*\*[^*]+\*\* \[\[[^\]]+\]\]$").unwrap();
+    static ref PROPERTY: Regex = Regex::new(r"^[^\s:-][^\s:]*::").unwrap();
+    static ref LINK: Regex = Regex::new(r"\[\[(?<name>[^\]]+)\]\]").unwrap();
+}
+
+/// Indentation unit of the nested blocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indent {
+    Tab,
+    Spaces(usize),
+}
+
+impl Indent {
+    fn unit(&self) -> String {
+        match self {
+            Indent::Tab => "\t".to_owned(),
+            Indent::Spaces(count) => " ".repeat(*count),
+        }
+    }
+
+    /// Detect the unit from the first nested block
+    fn detect(text: &str) -> Option<Self> {
+        text.lines().find_map(|line| {
+            let rest = line.trim_start();
+            if !(rest.starts_with("- ") || rest.starts_with("* ")) || rest.len() == line.len() {
+                return None;
+            }
+            match &line[..line.len() - rest.len()] {
+                whitespace if whitespace.starts_with('\t') => Some(Indent::Tab),
+                whitespace => Some(Indent::Spaces(whitespace.len())),
+            }
+        })
+    }
+
+    /// Convert the indentation of `block` from `self` to `to`, keeping the
+    /// alignment of the lines continuing a block
+    fn convert(&self, block: &str, to: Indent) -> String {
+        let (from, to) = (self.unit(), to.unit());
+        let mut depth = 0;
+
+        block
+            .lines()
+            .map(|line| {
+                let mut rest = line;
+                let mut units = 0;
+                while let Some(r) = rest.strip_prefix(from.as_str()) {
+                    rest = r;
+                    units += 1;
+                }
+
+                if rest.starts_with("- ") || rest == "-" {
+                    depth = units;
+                    format!("{}{rest}", to.repeat(units))
+                } else {
+                    let nested = units.min(depth);
+                    format!("{}{}{rest}", to.repeat(nested), from.repeat(units - nested))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl FromStr for Indent {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        match s {
+            "tab" => Ok(Indent::Tab),
+            "2" => Ok(Indent::Spaces(2)),
+            "4" => Ok(Indent::Spaces(4)),
+            _ => Err(PrepareError::format_invalid(
+                s,
+                "unknown indentation, expected tab, 2 or 4",
+            )),
+        }
+    }
+}
+
+/// Character starting the blocks, `-` as logseq writes them or `*`. The
+/// blocks are held with `-` whatever the one of their file
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Bullet {
+    #[default]
+    Dash,
+    Star,
+}
+
+impl Bullet {
+    fn char(&self) -> char {
+        match self {
+            Bullet::Dash => '-',
+            Bullet::Star => '*',
+        }
+    }
+
+    /// Detect the bullet of the first block
+    fn detect(text: &str) -> Option<Self> {
+        text.lines().find_map(|line| {
+            let line = line.trim_start();
+            [Bullet::Dash, Bullet::Star]
+                .into_iter()
+                .find(|bullet| is_bullet(line, bullet.char()))
+        })
+    }
+
+    /// Replace the bullet `from` of each line of `block` starting a block
+    /// with the one of `self`
+    fn convert(&self, block: &str, from: Bullet) -> String {
+        if *self == from {
+            return block.to_owned();
+        }
+        block
+            .lines()
+            .map(|line| {
+                let rest = line.trim_start();
+                match is_bullet(rest, from.char()) {
+                    true => format!(
+                        "{}{}{}",
+                        &line[..line.len() - rest.len()],
+                        self.char(),
+                        &rest[1..]
+                    ),
+                    false => line.to_owned(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Whether `line`, without its indentation, starts a block with `bullet`
+fn is_bullet(line: &str, bullet: char) -> bool {
+    line.strip_prefix(bullet)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+impl FromStr for Bullet {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        match s {
+            "-" => Ok(Bullet::Dash),
+            "*" => Ok(Bullet::Star),
+            _ => Err(PrepareError::format_invalid(
+                s,
+                "unknown bullet, expected - or *",
+            )),
+        }
+    }
+}
+
+/// What becomes of a generated block emptied by hand, down to its `-` and
+/// its `generated:: true`, when it gets generated again
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Emptied {
+    /// Put the block back in place of the emptied one
+    #[default]
+    Restore,
+    /// Leave it emptied, without adding the block again
+    Skip,
+}
+
+#[derive(Debug, Clone)]
 pub struct Page {
     path: PathBuf,
     content: Content,
+    source: Option<String>,
+    mark_generated: bool,
+    collapse_embeds: bool,
 }
 
 impl Page {
@@ -17,36 +225,595 @@ impl Page {
         Self {
             path: path.to_path_buf(),
             content: Default::default(),
+            source: None,
+            mark_generated: false,
+            collapse_embeds: false,
         }
     }
 
-    pub fn write(&mut self) -> Result<()> {
-        let mut file = std::fs::File::create(&self.path)?;
-        write!(file, "{}", self.content)?;
-        Ok(())
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The page at `path` holding `source`, the text of its file
+    pub fn from_source(path: &Path, source: String) -> Result<Page, PrepareError> {
+        let mut page = Page::new(path);
+        page.content = without_bom(&source)
+            .parse()
+            .map_err(|e: PrepareError| e.in_file(path))?;
+        page.source = Some(source);
+
+        Ok(page)
+    }
+
+    pub fn mark_generated(mut self, mark: bool) -> Self {
+        self.mark_generated = mark;
+        self
+    }
+
+    /// Fold the embeds pushed to the page with `collapsed:: true`
+    pub fn collapse_embeds(mut self, collapse: bool) -> Self {
+        self.collapse_embeds = collapse;
+        self
+    }
+
+    pub fn indent(mut self, indent: Option<Indent>) -> Self {
+        self.content.indent = indent;
+        self
+    }
+
+    /// Bullet of the blocks. Existing pages keep their own
+    pub fn bullet(mut self, bullet: Option<Bullet>) -> Self {
+        self.content.bullet = bullet;
+        self
+    }
+
+    /// Metadata keys kept as they are when merged into an existing page
+    /// that has them
+    pub fn preserve_metadata(mut self, keys: &[String]) -> Self {
+        self.content.preserve = keys.to_vec();
+        self
+    }
+
+    /// Sort the items of the list metadata, like `alias::`, when pushed and
+    /// after merging them into an existing page
+    pub fn sort_lists(mut self, sort: bool) -> Self {
+        self.content.sort_lists = sort;
+        self
+    }
+
+    /// Blank lines between the top-level blocks, replacing the spacing of
+    /// the existing page on merge
+    pub fn block_spacing(mut self, spacing: Option<usize>) -> Self {
+        self.content.spacing = spacing;
+        self
+    }
+
+    /// What to do with the generated blocks emptied by hand, only told
+    /// apart with the generated marker
+    pub fn emptied_blocks(mut self, emptied: Emptied) -> Self {
+        self.content.emptied = emptied;
+        self
+    }
+
+    /// End the file with exactly one newline, or none, instead of as parsed
+    pub fn final_newline(mut self, final_newline: Option<bool>) -> Self {
+        self.content.final_newline = final_newline;
+        self
+    }
+
+    /// Write the empty block of the format first, or not with `Some(false)`
+    /// for the logseq versions that read the page properties without it
+    pub fn leading_block(mut self, leading_block: Option<bool>) -> Self {
+        self.content.leading_block = leading_block;
+        self
+    }
+
+    /// This page with the content of `other` instead of its own, to write
+    /// `other` over it
+    pub fn overwrite(mut self, other: Page) -> Self {
+        self.content = other.content;
+        self
+    }
+
+    pub fn render(&self) -> String {
+        self.content.to_string()
+    }
+
+    /// Whether writing the page would change the file it was read from
+    pub fn is_modified(&self) -> bool {
+        self.source.as_deref() != Some(self.render().as_str())
+    }
+
+    #[cfg(test)]
+    pub fn write(&mut self) -> Result<(), PrepareError> {
+        self.write_retrying(0)
+    }
+
+    /// Write the page, again up to `retries` times on a transient error
+    pub fn write_retrying(&mut self, retries: u32) -> Result<(), PrepareError> {
+        retry(retries, || {
+            std::fs::File::create(&self.path).and_then(|mut file| write!(file, "{}", self.content))
+        })
+        .map_err(|source| PrepareError::Io {
+            path: self.path.clone(),
+            source,
+        })
     }
 
     pub fn push_content<C: Display>(&mut self, content: C) {
-        self.content.content.push(format!("- {}", content))
+        let mut block = format!("- {}", content);
+        if self.collapse_embeds && block.starts_with("- {{embed ") {
+            block = format!("{block}\n  {COLLAPSED} true");
+        }
+        self.content.content.push(self.mark(block));
+    }
+
+    /// Push a block with nested children, indented like the page
+    pub fn push_nested<P: Display, C: Display>(&mut self, parent: P, children: &[C]) {
+        let unit = self.content.indent.get_or_insert(Indent::Spaces(2)).unit();
+        let block = children
+            .iter()
+            .fold(format!("- {}", parent), |block, child| {
+                format!("{block}\n{unit}- {child}")
+            });
+        self.content.content.push(self.mark(block));
+    }
+
+    /// Push the task `task` scheduled on `date`, e.g. `<2024-09-01 Sun>`,
+    /// its `SCHEDULED:` line indented like the page
+    pub fn push_scheduled<T: Display, D: Display>(&mut self, task: T, date: D) {
+        let unit = self.content.indent.get_or_insert(Indent::Spaces(2)).unit();
+        let block = format!("- {task}\n{unit}SCHEDULED: <{date}>");
+        self.content.content.push(self.mark(block));
+    }
+
+    /// Replace the line `- {old}` of the generated blocks of the existing
+    /// page with `- {new}` when merging into it, e.g. the embed of a day
+    /// page by the embed of its first block, not to have both
+    pub fn supersede(&mut self, old: String, new: String) {
+        self.content.superseded.push((old, new));
+    }
+
+    /// Push blocks written by hand, never marked as generated
+    pub fn push_blocks(&mut self, blocks: Vec<String>) {
+        self.content.content.extend(blocks);
+    }
+
+    pub fn push_template(&mut self, text: &str) -> Result<()> {
+        let mut template: Content = text.parse()?;
+        template.spacing = None;
+        template.content = template
+            .content
+            .into_iter()
+            .map(|block| self.mark(block))
+            .collect();
+        self.content = std::mem::take(&mut self.content) + template;
+        Ok(())
+    }
+
+    fn mark(&self, block: String) -> String {
+        if !self.mark_generated {
+            return block;
+        }
+
+        // after the SCHEDULED: or DEADLINE: lines, which logseq expects
+        // right under the first one
+        let marker = format!("  {GENERATED}");
+        let mut lines = block.lines().collect::<Vec<_>>();
+        let planning = lines
+            .iter()
+            .skip(1)
+            .take_while(|line| PLANNING.iter().any(|p| line.trim_start().starts_with(p)))
+            .count();
+        lines.insert((1 + planning).min(lines.len()), &marker);
+        lines.join("\n")
+    }
+
+    /// Rewrite the page in the canonical format, without changing what it
+    /// holds: the metadata sorted by key, a single empty block at the top,
+    /// unless left out with `leading_block`
+    pub fn normalize(&mut self) {
+        self.content.metadata.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut first = self.content.leading_block != Some(false);
+        self.content
+            .content
+            .retain(|block| std::mem::take(&mut first) || block.trim() != "-");
+    }
+
+    /// The page with the link metadata of `links`, like `next::` or
+    /// `parent::`, instead of its own, its other metadata and its blocks
+    /// staying as they are. Only the link metadata linking to the pages
+    /// `names` tells generated is replaced or removed, a hand-written
+    /// `parent:: [[Project]]` is kept
+    pub fn relink<N>(mut self, links: Page, names: N) -> Page
+    where
+        N: Fn(&str) -> bool,
+    {
+        let is_link = |metadata: &Metadata| LINK_KEYS.contains(&metadata.key.as_str());
+        let is_generated = |metadata: &Metadata| match &metadata.value {
+            Value::Text(text) if is_link(metadata) => {
+                let mut links = LINK.captures_iter(text).peekable();
+                links.peek().is_some() && links.all(|caps| names(&caps["name"]))
+            }
+            _ => false,
+        };
+        self.content.final_newline = links.content.final_newline;
+        let links = links
+            .content
+            .metadata
+            .into_iter()
+            .filter(is_link)
+            .collect::<Vec<_>>();
 
+        let metadata = &mut self.content.metadata;
+        metadata.retain(|m| !is_generated(m) || links.iter().any(|link| link.key == m.key));
+        for link in links {
+            match metadata.iter_mut().find(|m| m.key == link.key) {
+                Some(m) if is_generated(m) => m.update(link),
+                Some(_) => {}
+                None => metadata.push(link),
+            }
+        }
+        self
     }
 
     pub fn push_metadata<M: Into<Metadata>>(&mut self, metadata: M) {
-        self.content.metadata.push(metadata.into());
+        let mut metadata = metadata.into();
+        if self.content.sort_lists {
+            metadata.sort_list();
+        }
+        self.content.metadata.push(metadata);
+    }
+
+    /// Set the metadata, replacing the value of its key if the page has it
+    /// whatever its merge policy
+    pub fn set_metadata<M: Into<Metadata>>(&mut self, metadata: M) {
+        let metadata = metadata.into();
+        match self
+            .content
+            .metadata
+            .iter_mut()
+            .find(|m| m.key == metadata.key)
+        {
+            Some(existing) if existing.value == metadata.value => return,
+            Some(existing) => existing.value = metadata.value,
+            None => self.content.metadata.push(metadata),
+        }
+        self.content.header = None;
+    }
+
+    /// Rewrite the links of the generated content, see
+    /// [`Content::rewrite_links`]
+    pub fn rewrite_links<F, N>(&mut self, rename: F, names: N) -> Vec<(String, String)>
+    where
+        F: Fn(&str) -> Option<String>,
+        N: Fn(&str) -> bool,
+    {
+        self.content.rewrite_links(rename, names)
+    }
+
+    /// Rename the pages this page links to, in all its blocks, link metadata
+    /// and filters, `rename` giving the new name of a page, if any. Unlike
+    /// [`Page::rewrite_links`], meant for the pages being generated
+    pub fn rename_links<F>(&mut self, rename: F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        for block in &mut self.content.content {
+            *block = replace_links(block, &rename);
+        }
+        for metadata in &mut self.content.metadata {
+            match &mut metadata.value {
+                Value::Text(text) if LINK_KEYS.contains(&metadata.key.as_str()) => {
+                    *text = replace_links(text, &rename);
+                }
+                Value::Filters(filters) => filters.rename(&rename),
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether everything on the page was generated by this tool, see
+    /// [`Content::is_generated_only`]
+    pub fn is_generated_only<N, G>(&self, names: N, generates: G) -> bool
+    where
+        N: Fn(&str) -> bool,
+        G: Fn(&Metadata) -> bool,
+    {
+        self.content.is_generated_only(names, generates)
+    }
+
+    /// Whether the page has no block beside empty ones, whatever its metadata
+    pub fn is_blank(&self) -> bool {
+        self.content
+            .content
+            .iter()
+            .all(|block| unmarked(block).trim() == "-")
+    }
+
+    /// Every page linked from this one, in metadata, blocks and embeds, in
+    /// order of first appearance
+    pub fn links(&self) -> Vec<Link> {
+        let mut names = Vec::<String>::new();
+        for caps in LINK.captures_iter(&self.render()) {
+            if !names.iter().any(|name| *name == caps["name"]) {
+                names.push(caps["name"].to_owned());
+            }
+        }
+        names.into_iter().map(|name| Link { name }).collect()
+    }
+
+    /// The first line of every block, the nested ones included, without
+    /// their `- ` and without the empty blocks
+    pub fn block_heads(&self) -> Vec<&str> {
+        self.content
+            .content
+            .iter()
+            .flat_map(|block| block.lines())
+            .filter_map(|line| line.trim_start().strip_prefix("- "))
+            .filter(|head| !head.trim().is_empty())
+            .collect()
+    }
+
+    /// The unfinished TODO and DOING task blocks, with their children but
+    /// without their `id::` so that they can be copied to another page
+    pub fn tasks(&self) -> Vec<String> {
+        self.content
+            .content
+            .iter()
+            .filter(|block| {
+                block
+                    .lines()
+                    .next()
+                    .and_then(|first| first.strip_prefix("- "))
+                    .is_some_and(|head| is_task(head, &OPEN[..2]))
+            })
+            .map(|block| {
+                block
+                    .lines()
+                    .filter(|line| !line.trim().starts_with("id::") && line.trim() != GENERATED)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect()
+    }
+
+    /// Whether a block marked as generated embeds a block, e.g. `{{embed
+    /// ((66d4a5e1-...))}}`
+    pub fn embeds_marked_blocks(&self) -> bool {
+        self.content.content.iter().any(|block| {
+            is_marked(block)
+                && block
+                    .lines()
+                    .any(|line| line.trim_start().starts_with("- {{embed (("))
+        })
+    }
+
+    /// The `id::` of the first block, to embed or reference it, skipping
+    /// the empty ones
+    pub fn first_block_id(&self) -> Option<String> {
+        let block = self
+            .content
+            .content
+            .iter()
+            .find(|block| unmarked(block).trim() != "-")?;
+        block.lines().skip(1).find_map(|line| {
+            let (key, value) = line.trim().split_once("::")?;
+            (key == "id").then(|| value.trim().to_owned())
+        })
+    }
+
+    /// Changes from this page to `other`
+    pub fn diff(&self, other: &Page) -> Diff {
+        self.content.diff(&other.content)
+    }
+
+    /// The content of the page to write next to it, with --sidecar
+    pub fn sidecar(&self) -> Sidecar<'_> {
+        Sidecar(&self.content)
+    }
+
+    /// The metadata both pages have with a different text, as `(key, ours,
+    /// theirs)`, the filters and lists being merged instead
+    pub fn conflicts(&self, other: &Page) -> Vec<(String, String, String)> {
+        self.content
+            .metadata
+            .iter()
+            .filter_map(|ours| {
+                let theirs = other.content.metadata.iter().find(|m| m.key == ours.key)?;
+                match (&ours.value, &theirs.value) {
+                    (Value::Text(a), Value::Text(b)) if a != b => {
+                        Some((ours.key.clone(), a.clone(), b.clone()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// How the page was parsed, to display it
+    pub fn inspect<N>(&self, names: N) -> Inspect<'_>
+    where
+        N: Fn(&str) -> bool,
+    {
+        let generated = self
+            .content
+            .content
+            .iter()
+            .map(|block| self.content.is_generated(block, &names))
+            .collect();
+        Inspect(&self.content, generated)
+    }
+}
+
+/// Metadata and blocks of a page as parsed, each line of the blocks with
+/// the nesting level of the block it belongs to, and whether each block was
+/// generated
+pub struct Inspect<'a>(&'a Content, Vec<bool>);
+
+impl Display for Inspect<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let content = self.0;
+        match content.indent {
+            Some(Indent::Tab) => writeln!(f, "indent: tab")?,
+            Some(Indent::Spaces(count)) => writeln!(f, "indent: {count} spaces")?,
+            None => writeln!(f, "indent: none")?,
+        }
+
+        writeln!(f, "metadata:")?;
+        for metadata in &content.metadata {
+            writeln!(f, "  {} = {}", metadata.key, metadata.value)?;
+        }
+
+        writeln!(f, "blocks:")?;
+        let unit = content.indent.unwrap_or(Indent::Spaces(2)).unit();
+        for (index, block) in content.content.iter().enumerate() {
+            match self.1[index] {
+                true => writeln!(f, "  #{}, generated", index + 1)?,
+                false => writeln!(f, "  #{}", index + 1)?,
+            }
+
+            let mut depth = 0;
+            for line in block.lines() {
+                let mut rest = line;
+                let mut units = 0;
+                while let Some(r) = rest.strip_prefix(unit.as_str()) {
+                    rest = r;
+                    units += 1;
+                }
+                if rest.starts_with("- ") || rest == "-" {
+                    depth = units;
+                }
+                writeln!(
+                    f,
+                    "    {depth} | {}",
+                    &line[units.min(depth) * unit.len()..]
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The content of a page as JSON, with --sidecar: its metadata as an
+/// object and its blocks as strings, without the empty ones
+pub struct Sidecar<'a>(&'a Content);
+
+impl Display for Sidecar<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let metadata = self
+            .0
+            .metadata
+            .iter()
+            .map(|metadata| {
+                format!(
+                    "    {}: {}",
+                    json_string(&metadata.key),
+                    metadata.value.to_json()
+                )
+            })
+            .collect::<Vec<_>>();
+        let blocks = self
+            .0
+            .content
+            .iter()
+            .filter(|block| unmarked(block).trim() != "-")
+            .map(|block| format!("    {}", json_string(block)))
+            .collect::<Vec<_>>();
+
+        writeln!(f, "{{")?;
+        for (key, items, open, close) in [
+            ("metadata", metadata, '{', '}'),
+            ("blocks", blocks, '[', ']'),
+        ] {
+            let end = if key == "metadata" { "," } else { "" };
+            match items.is_empty() {
+                true => writeln!(f, "  \"{key}\": {open}{close}{end}")?,
+                false => {
+                    writeln!(f, "  \"{key}\": {open}")?;
+                    writeln!(f, "{}", items.join(",\n"))?;
+                    writeln!(f, "  {close}{end}")?;
+                }
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Diff {
+    pub added_metadata: Vec<Metadata>,
+    pub changed_metadata: Vec<(Metadata, Metadata)>,
+    pub removed_metadata: Vec<Metadata>,
+    pub added_blocks: Vec<String>,
+    pub removed_blocks: Vec<String>,
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for metadata in &self.added_metadata {
+            writeln!(f, "+ {}", metadata)?;
+        }
+        for (old, new) in &self.changed_metadata {
+            writeln!(f, "~ {} -> {}", old, new.value)?;
+        }
+        for metadata in &self.removed_metadata {
+            writeln!(f, "- {}", metadata)?;
+        }
+        for block in &self.added_blocks {
+            writeln!(f, "+ {}", block.replace('\n', "\n  "))?;
+        }
+        for block in &self.removed_blocks {
+            writeln!(f, "- {}", block.replace('\n', "\n  "))?;
+        }
+        Ok(())
     }
 }
 
 impl TryFrom<&Path> for Page {
-    type Error = anyhow::Error;
+    type Error = PrepareError;
 
-    fn try_from(path: &Path) -> Result<Page> {
-        let mut page = Page::new(path);
-        page.content = std::fs::read_to_string(path)
-            .with_context(|| format!("reading file {:?}", path))?
-            .parse()
-            .with_context(|| format!("reading file {:?}", path))?;
+    fn try_from(path: &Path) -> Result<Page, PrepareError> {
+        let source = std::fs::read_to_string(path).map_err(|source| PrepareError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Page::from_source(path, source)
+    }
+}
 
-        Ok(page)
+impl Page {
+    /// Merge `rhs` into the page without touching its blocks: the text of
+    /// the existing file is kept byte for byte, the new blocks of `rhs`
+    /// being appended at the end. The metadata is still updated, unless
+    /// `freeze_metadata`
+    pub fn append(self, rhs: Page, freeze_metadata: bool) -> Page {
+        let Some(source) = self.source.as_deref().map(without_bom) else {
+            return self + rhs;
+        };
+        let start = source
+            .split_inclusive('\n')
+            .take_while(|line| !line.starts_with(['-', '*']))
+            .map(str::len)
+            .sum();
+        let (header, body) = source.split_at(start);
+
+        let existing = self.content.clone();
+        let mut merged = self.content.clone() + rhs.content;
+        merged.content.retain(|block| {
+            let block = unmarked(block);
+            block.trim() != "-" && existing.content.iter().all(|b| unmarked(b) != block)
+        });
+        if freeze_metadata || merged.metadata == existing.metadata {
+            merged.header = Some(header.to_owned());
+        }
+        merged.body = Some(body.to_owned());
+
+        Page {
+            content: merged,
+            ..self
+        }
     }
 }
 
@@ -59,92 +826,489 @@ impl Add for Page {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Content {
     metadata: Vec<Metadata>,
     content: Vec<String>,
+    indent: Option<Indent>,
+    bullet: Option<Bullet>,
+    spacing: Option<usize>,
+    preserve: Vec<String>,
+    /// Sort the list values, instead of keeping the order they were added in
+    sort_lists: bool,
+    /// Text of the existing metadata, written as is by [`Page::append`]
+    header: Option<String>,
+    /// Text of the existing blocks, written as is before the content by
+    /// [`Page::append`]
+    body: Option<String>,
+    emptied: Emptied,
+    /// End with exactly one newline, or none, instead of the blank line
+    /// after the metadata of the pages without blocks
+    final_newline: Option<bool>,
+    leading_block: Option<bool>,
+    /// Lines of the generated blocks replaced by other ones on merge, as
+    /// `(old, new)`, see [`Page::supersede`]
+    superseded: Vec<(String, String)>,
 }
 
-impl Display for Content {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for line in &self.metadata {
-            writeln!(f, "{}", line)?;
-        }
-        writeln!(f)?;
-
-        for (index, line) in self.content.iter().enumerate() {
-            if index == 0 && line != "-" {
-                writeln!(f, "-")?;
-            }
-
-            writeln!(f, "{}", line)?;
+impl Content {
+    /// Whether the block was written by this tool: it carries the generated
+    /// marker or, on pages without any marked block, looks like a generated
+    /// embed or link to a page `names` tells generated, e.g. `2024/Week 36`,
+    /// possibly captioned like `**Monday** [[2024-09-02]]` and with nested
+    /// embeds or links
+    pub fn is_generated<N>(&self, block: &str, names: N) -> bool
+    where
+        N: Fn(&str) -> bool,
+    {
+        if is_marked(block) {
+            return true;
         }
-        Ok(())
+        let block = unmarked(block);
+        let mut lines = block.lines();
+        !self.content.iter().any(|b| is_marked(b))
+            && lines
+                .next()
+                .is_some_and(|line| GENERATED_SYNTAX.is_match(line) || CAPTION.is_match(line))
+            && lines.all(|line| {
+                line.starts_with(char::is_whitespace)
+                    && GENERATED_SYNTAX.is_match(line.trim_start())
+            })
+            && LINK.captures_iter(&block).all(|caps| names(&caps["name"]))
     }
-}
 
-impl FromStr for Content {
-    type Err = anyhow::Error;
+    /// Whether everything on the page was generated by this tool, so that
+    /// deleting it loses nothing, see [`Content::is_generated`]. The
+    /// metadata like `title::`, also written by hand, only counts when
+    /// `generates` tells it has the generated value
+    pub fn is_generated_only<N, G>(&self, names: N, generates: G) -> bool
+    where
+        N: Fn(&str) -> bool,
+        G: Fn(&Metadata) -> bool,
+    {
+        self.metadata.iter().all(|metadata| {
+            let key = metadata.key.as_str();
+            LINK_KEYS.contains(&key)
+                || GENERATED_KEYS.contains(&key)
+                || (SHARED_KEYS.contains(&key) && generates(metadata))
+        }) && self
+            .content
+            .iter()
+            .all(|block| block.trim() == "-" || self.is_generated(block, &names))
+    }
 
-    fn from_str(string: &str) -> Result<Self> {
-        let mut page = Content::default();
-        let mut read_content = false;
-        let mut content = String::new();
+    pub fn diff(&self, other: &Content) -> Diff {
+        let mut diff = Diff::default();
 
-        for line in string.lines() {
-            if !read_content {
-                if line.starts_with("-") {
-                    read_content = true;
-                    content = line.to_owned();
-                } else if !line.is_empty() {
-                    page.metadata.push(line.parse()?);
+        for metadata in &other.metadata {
+            match self.metadata.iter().find(|m| m.key == metadata.key) {
+                None => diff.added_metadata.push(metadata.clone()),
+                Some(old) if old.value != metadata.value => {
+                    diff.changed_metadata.push((old.clone(), metadata.clone()))
                 }
-            } else if line.starts_with("- ") {
-                page.content.push(content);
-                content = line.to_owned();
-            } else {
-                content.push('\n');
-                content.push_str(line);
+                Some(_) => {}
             }
         }
-        if read_content {
-            page.content.push(content);
-        }
+        diff.removed_metadata = self
+            .metadata
+            .iter()
+            .filter(|m| other.metadata.iter().all(|n| n.key != m.key))
+            .cloned()
+            .collect();
 
-        Ok(page)
+        diff.added_blocks = other
+            .content
+            .iter()
+            .filter(|block| !self.content.contains(block))
+            .cloned()
+            .collect();
+        diff.removed_blocks = self
+            .content
+            .iter()
+            .filter(|block| !other.content.contains(block))
+            .cloned()
+            .collect();
+
+        diff
     }
-}
 
-impl Add for Content {
-    type Output = Content;
+    /// Rewrite the links of the generated blocks and metadata, `rename`
+    /// giving the new name of a linked page, if any, and `names` telling the
+    /// generated ones. Returns the old and new names of each rewritten link
+    pub fn rewrite_links<F, N>(&mut self, rename: F, names: N) -> Vec<(String, String)>
+    where
+        F: Fn(&str) -> Option<String>,
+        N: Fn(&str) -> bool,
+    {
+        let mut renamed = Vec::new();
+        let mut rewrite = |text: &str| {
+            replace_links(text, |old| {
+                let name = rename(old)?;
+                renamed.push((old.to_owned(), name.clone()));
+                Some(name)
+            })
+        };
 
-    fn add(mut self, rhs: Content) -> Self::Output {
-        for line in rhs.metadata {
-            if let Some(metadata) = self.metadata.iter_mut().find(|l| l.key == line.key) {
-                metadata.update(line);
-            } else {
-                self.metadata.push(line);
+        let generated = self
+            .content
+            .iter()
+            .map(|block| self.is_generated(block, &names))
+            .collect::<Vec<_>>();
+        for (block, generated) in self.content.iter_mut().zip(generated) {
+            if generated {
+                *block = rewrite(block);
             }
         }
-        for line in rhs.content {
-            if self.content.iter().all(|l| *l != line) {
-                self.content.push(line);
+
+        for metadata in &mut self.metadata {
+            if let (true, Value::Text(text)) = (
+                LINK_KEYS.contains(&metadata.key.as_str()),
+                &mut metadata.value,
+            ) {
+                *text = rewrite(text);
             }
         }
-        self
+
+        renamed
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use assert_fs::prelude::*;
-    use indoc::{formatdoc, indoc};
+/// `text` with its links renamed, `rename` giving the new name of a linked
+/// page, if any
+fn replace_links<F>(text: &str, mut rename: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    LINK.replace_all(text, |caps: &Captures| match rename(&caps["name"]) {
+        Some(name) => format!("[[{name}]]"),
+        None => caps[0].to_owned(),
+    })
+    .into_owned()
+}
 
-    #[test]
-    fn page() -> anyhow::Result<()> {
-        let temp_dir = assert_fs::TempDir::new()?;
-        let file = temp_dir.child("page.md");
+/// Whether `head`, the first line of a block, is a task marked with one of
+/// `markers`
+pub fn is_task(head: &str, markers: &[&str]) -> bool {
+    markers.iter().any(|marker| {
+        head.strip_prefix(marker)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    })
+}
+
+/// The text of a task block after its marker, e.g. `Review` for `- DONE
+/// Review`, whatever its state
+fn task(block: &str) -> Option<&str> {
+    let head = block.lines().next()?.strip_prefix("- ")?;
+    let (marker, text) = head.split_once(' ')?;
+    OPEN.iter()
+        .chain(&CLOSED)
+        .any(|m| *m == marker)
+        .then_some(text)
+}
+
+/// `block` with its lines `- {old}` replaced by `- {new}`, for each of
+/// `superseded`, whatever their indentation
+fn supersede(block: &str, superseded: &[(String, String)]) -> String {
+    block
+        .lines()
+        .map(|line| {
+            let rest = line.trim_start();
+            let indent = &line[..line.len() - rest.len()];
+            match superseded
+                .iter()
+                .find(|(old, _)| rest.strip_prefix("- ") == Some(old.as_str()))
+            {
+                Some((_, new)) => format!("{indent}- {new}"),
+                None => line.to_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_marked(block: &str) -> bool {
+    block.lines().skip(1).any(|line| line.trim() == GENERATED)
+}
+
+fn is_collapsed_line(line: &str) -> bool {
+    line.trim().starts_with(COLLAPSED)
+}
+
+/// The `new` block with the folding state of the `old` one
+fn keep_collapsed(old: &str, new: &str) -> String {
+    let mut lines = new.lines();
+    let first = lines.next().unwrap_or_default();
+
+    std::iter::once(first)
+        .chain(old.lines().filter(|line| is_collapsed_line(line)))
+        .chain(lines.filter(|line| !is_collapsed_line(line)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `text` without the byte order mark some editors start UTF-8 files with:
+/// the pages are read and written as UTF-8 without it
+fn without_bom(text: &str) -> &str {
+    text.strip_prefix('\u{feff}').unwrap_or(text)
+}
+
+/// Page linked by a block only made of the link to it
+fn linked(block: &str) -> Option<&str> {
+    let name = block.strip_prefix("- [[")?.strip_suffix("]]")?;
+    (!name.contains("]]")).then_some(name)
+}
+
+/// Page embedded by a block only made of the embed of it
+fn embedded(block: &str) -> Option<&str> {
+    let name = block.strip_prefix("- {{embed [[")?.strip_suffix("]]}}")?;
+    (!name.contains("]]")).then_some(name)
+}
+
+/// The block without the generated marker nor its folding state, to compare
+/// the blocks by content
+fn unmarked(block: &str) -> String {
+    block
+        .lines()
+        .filter(|line| line.trim() != GENERATED && !is_collapsed_line(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Display for Content {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(final_newline) = self.final_newline else {
+            return self.write_text(f);
+        };
+        let mut text = String::new();
+        self.write_text(&mut text)?;
+        write!(f, "{}", text.trim_end_matches('\n'))?;
+        if final_newline {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Content {
+    /// Empty block written first on every page, so that logseq reads the
+    /// metadata above it as page properties, unless left out
+    fn leading_block(&self) -> Option<&'static str> {
+        Some("-").filter(|_| self.leading_block != Some(false))
+    }
+
+    fn write_text<W: std::fmt::Write>(&self, f: &mut W) -> std::fmt::Result {
+        match &self.header {
+            Some(header) => write!(f, "{header}")?,
+            // the blank line only separates the metadata from the blocks
+            None if self.metadata.is_empty() => {}
+            None => {
+                for line in &self.metadata {
+                    writeln!(f, "{}", line)?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        let spacing = "\n".repeat(self.spacing.unwrap_or_default());
+        let bullet = self.bullet.unwrap_or_default();
+        let body = self.body.as_deref().filter(|body| !body.is_empty());
+        if let Some(body) = body {
+            write!(f, "{body}")?;
+            if !body.ends_with('\n') && !self.content.is_empty() {
+                writeln!(f)?;
+            }
+        }
+        for (index, line) in self.content.iter().enumerate() {
+            if body.is_some() {
+                write!(f, "{spacing}")?;
+            } else if let (0, Some(leading)) = (index, self.leading_block()) {
+                if line != leading {
+                    writeln!(f, "{}", bullet.convert(leading, Bullet::Dash))?;
+                    write!(f, "{spacing}")?;
+                }
+            } else if index > 0 {
+                write!(f, "{spacing}")?;
+            }
+
+            writeln!(f, "{}", bullet.convert(line, Bullet::Dash))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Content {
+    type Err = PrepareError;
+
+    fn from_str(string: &str) -> Result<Self, PrepareError> {
+        let mut page = Content::default();
+        let mut read_content = false;
+        let mut content = String::new();
+        let bullet = Bullet::detect(string).unwrap_or_default();
+
+        for (index, line) in string.lines().enumerate() {
+            if !read_content {
+                if PROPERTY.is_match(line) {
+                    let metadata = line.parse().map_err(|e: PrepareError| e.at_line(index))?;
+                    page.metadata.push(metadata);
+                } else if !line.is_empty() {
+                    // Text that isn't a `key:: value` starts the content,
+                    // even without a leading `-`
+                    read_content = true;
+                    content = line.to_owned();
+                }
+            } else if is_bullet(line.trim_end(), bullet.char()) {
+                let block = content.trim_end_matches('\n');
+                if block.len() < content.len() {
+                    page.spacing = Some(1);
+                }
+                page.content.push(Bullet::Dash.convert(block, bullet));
+                page.spacing.get_or_insert(0);
+                content = line.to_owned();
+            } else {
+                content.push('\n');
+                content.push_str(line);
+            }
+        }
+        if read_content {
+            let block = content.trim_end_matches('\n');
+            page.content.push(Bullet::Dash.convert(block, bullet));
+        }
+        page.indent = Indent::detect(string);
+        page.bullet = Bullet::detect(string);
+
+        Ok(page)
+    }
+}
+
+impl Add for Content {
+    type Output = Content;
+
+    fn add(mut self, mut rhs: Content) -> Self::Output {
+        // Keep the nested blocks indented like the existing ones
+        match (self.indent, rhs.indent) {
+            (Some(to), Some(from)) if to != from => {
+                rhs.content = rhs
+                    .content
+                    .iter()
+                    .map(|block| from.convert(block, to))
+                    .collect();
+            }
+            (None, indent) => self.indent = indent,
+            _ => {}
+        }
+        // and their blocks with the same bullet
+        if self.bullet.is_none() {
+            self.bullet = rhs.bullet;
+        }
+        if rhs.spacing.is_some() {
+            self.spacing = rhs.spacing;
+        }
+        if rhs.final_newline.is_some() {
+            self.final_newline = rhs.final_newline;
+        }
+        if rhs.leading_block.is_some() {
+            self.leading_block = rhs.leading_block;
+        }
+
+        for line in rhs.metadata {
+            if let Some(metadata) = self.metadata.iter_mut().find(|l| l.key == line.key) {
+                if !rhs.preserve.contains(&line.key) {
+                    metadata.update(line);
+                }
+            } else {
+                self.metadata.push(line);
+            }
+        }
+        if rhs.sort_lists {
+            self.metadata.iter_mut().for_each(Metadata::sort_list);
+        }
+        // the blocks only match ones just generated, whatever their names
+        let generated = self
+            .content
+            .iter()
+            .map(|block| self.is_generated(block, |_| true))
+            .collect::<Vec<_>>();
+        for (block, _) in self.content.iter_mut().zip(&generated).filter(|(_, g)| **g) {
+            *block = supersede(block, &rhs.superseded);
+        }
+        // Generated blocks emptied by hand, in order, each standing for the
+        // first generated block missing from the page
+        let mut emptied = self
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| is_marked(block) && unmarked(block).trim() == "-")
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        // A block is only deduplicated against an existing one with the same
+        // text at the same level: the top-level blocks are compared whole,
+        // with their children, so that a nested block never matches a
+        // top-level one, nor a child of another parent
+        for line in rhs.content {
+            let block = unmarked(&line);
+            match self.content.iter().position(|l| unmarked(l) == block) {
+                // Upgrade previously generated blocks to carry the marker,
+                // keeping their folding state
+                Some(index) if generated[index] && is_marked(&line) => {
+                    self.content[index] = keep_collapsed(&self.content[index], &line);
+                }
+                Some(_) => {}
+                // A generated task stays the same block once its state
+                // changed, e.g. marked DONE
+                None if is_marked(&line)
+                    && task(&block).is_some_and(|text| {
+                        self.content
+                            .iter()
+                            .any(|l| is_marked(l) && task(&unmarked(l)) == Some(text))
+                    }) => {}
+                // Upgrade a previously generated link to the embed of the
+                // same page, e.g. once the page exists with
+                // --embed-existing-only
+                None => match embedded(&block).and_then(|name| {
+                    self.content
+                        .iter()
+                        .zip(&generated)
+                        .position(|(l, generated)| *generated && linked(&unmarked(l)) == Some(name))
+                }) {
+                    Some(index) => self.content[index] = line,
+                    None if is_marked(&line) => match (emptied.next(), rhs.emptied) {
+                        (Some(index), Emptied::Restore) => self.content[index] = line,
+                        (Some(_), Emptied::Skip) => {}
+                        (None, _) => self.content.push(line),
+                    },
+                    None => self.content.push(line),
+                },
+            }
+        }
+
+        // Keep at most one empty block, at the very top
+        let mut first = true;
+        self.content
+            .retain(|block| std::mem::take(&mut first) || block.trim() != "-");
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{List, ToMetadata};
+    use assert_fs::prelude::*;
+    use indoc::{formatdoc, indoc};
+
+    /// The names of the day and period pages of the tests, e.g. `2024-09-01`
+    /// or `2024/September`
+    fn names(name: &str) -> bool {
+        name.starts_with("2024")
+    }
+
+    #[test]
+    fn page() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
 
         let metadata = indoc! {r#"
             month:: [[2024/September]]
@@ -168,7 +1332,9 @@ mod tests {
         )?;
 
         let mut page: Page = file.path().try_into()?;
+        assert!(page.is_modified());
         page.write()?;
+        assert!(!Page::try_from(file.path())?.is_modified());
         file.assert(formatdoc! {"
             {metadata}
             -
@@ -205,4 +1371,852 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn template() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+
+        let generate = || -> anyhow::Result<Page> {
+            let mut page = Page::new(file.path());
+            page.push_content("{{embed [[2024-09-01]]}}");
+            page.push_template("- ## Review\n  - What went well?\n")?;
+            Ok(page)
+        };
+
+        generate()?.write()?;
+        let mut page = Page::try_from(file.path())? + generate()?;
+        page.write()?;
+
+        file.assert(indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+            - ## Review
+              - What went well?
+        "});
+
+        Ok(())
+    }
+
+    #[test]
+    fn inspect() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("2024_09_02.md");
+        file.write_str(indoc! {r#"
+            week:: [[2024/Week 36]]
+            filters:: {"2024/Week 36" false}
+
+            -
+            - DONE Review
+              :LOGBOOK:
+              CLOCK: [2024-09-02 Mon 09:00]--[2024-09-02 Mon 09:30] =>  00:30:00
+              :END:
+              - with the team
+                continued
+                - and the notes
+            - {{embed [[2024-09-01]]}}
+        "#})?;
+
+        assert_eq!(
+            indoc! {r#"
+                indent: 2 spaces
+                metadata:
+                  week = [[2024/Week 36]]
+                  filters = {"2024/Week 36" false}
+                blocks:
+                  #1
+                    0 | -
+                  #2
+                    0 | - DONE Review
+                    0 |   :LOGBOOK:
+                    0 |   CLOCK: [2024-09-02 Mon 09:00]--[2024-09-02 Mon 09:30] =>  00:30:00
+                    0 |   :END:
+                    1 | - with the team
+                    1 |   continued
+                    2 | - and the notes
+                  #3, generated
+                    0 | - {{embed [[2024-09-01]]}}
+            "#},
+            Page::try_from(file.path())?.inspect(names).to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("2024___Week 36.md");
+        file.write_str(indoc! {"
+            next:: [[2024/Week 36]]
+            prev:: [[2024/Week 35]]
+            notes:: mine
+
+            -
+            - {{embed [[2024-09-02]]}}
+            - Some notes
+        "})?;
+        let page = Page::try_from(file.path())?;
+
+        let mut other = Page::new(file.path());
+        other.push_metadata("[[2024/Week 37]]".to_metadata("next"));
+        other.push_metadata("[[2024/Week 35]]".to_metadata("prev"));
+        other.push_metadata("[[2024/September]]".to_metadata("month"));
+        other.push_content("{{embed [[2024-09-02]]}}");
+        other.push_content("{{embed [[2024-09-03]]}}");
+
+        let diff = page.diff(&other);
+        assert_eq!(
+            vec!["[[2024/September]]".to_metadata("month")],
+            diff.added_metadata
+        );
+        assert_eq!(
+            vec![(
+                "[[2024/Week 36]]".to_metadata("next"),
+                "[[2024/Week 37]]".to_metadata("next")
+            )],
+            diff.changed_metadata
+        );
+        assert_eq!(vec!["mine".to_metadata("notes")], diff.removed_metadata);
+        assert_eq!(vec!["- {{embed [[2024-09-03]]}}"], diff.added_blocks);
+        assert_eq!(vec!["-", "- Some notes"], diff.removed_blocks);
+        assert_eq!(
+            indoc! {"
+                + month:: [[2024/September]]
+                ~ next:: [[2024/Week 36]] -> [[2024/Week 37]]
+                - notes:: mine
+                + - {{embed [[2024-09-03]]}}
+                - -
+                - - Some notes
+            "},
+            diff.to_string()
+        );
+
+        assert_eq!(Diff::default(), page.diff(&page));
+        assert_eq!(
+            vec!["- {{embed [[2024-09-03]]}}"],
+            page.diff(&(page.clone() + other)).added_blocks
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bullet() -> anyhow::Result<()> {
+        assert_eq!(Bullet::Star, "*".parse::<Bullet>()?);
+        assert!("+".parse::<Bullet>().is_err());
+
+        let dashes = "day:: Monday\n\n-\n- Parent\n  - Child\n  *not a bullet*\n- {{embed [[2024-09-01]]}}\n";
+        let stars = "day:: Monday\n\n*\n* Parent\n  * Child\n  *not a bullet*\n* {{embed [[2024-09-01]]}}\n";
+        assert_eq!(Some(Bullet::Dash), Bullet::detect(dashes));
+        assert_eq!(Some(Bullet::Star), Bullet::detect(stars));
+
+        // Round-trip preserves the bullet, the blocks being the same
+        for text in [dashes, stars] {
+            let content = text.parse::<Content>()?;
+            assert_eq!(text, content.to_string());
+            assert_eq!(
+                dashes.parse::<Content>()?.content,
+                content.content,
+                "{text:?}"
+            );
+        }
+
+        // and so does merging, deduplicating the blocks whatever their bullet
+        let path = Path::new("page.md");
+        let generated = |bullet| {
+            let mut page = Page::new(path).bullet(bullet);
+            page.push_content("{{embed [[2024-09-01]]}}");
+            page.push_content("{{embed [[2024-09-02]]}}");
+            page
+        };
+        let merged = Page::from_source(path, stars.to_owned())? + generated(None);
+        assert_eq!(
+            format!("{stars}* {{{{embed [[2024-09-02]]}}}}\n"),
+            merged.render()
+        );
+        let merged = Page::from_source(path, dashes.to_owned())? + generated(Some(Bullet::Star));
+        assert_eq!(
+            format!("{dashes}- {{{{embed [[2024-09-02]]}}}}\n"),
+            merged.render()
+        );
+
+        assert_eq!(
+            "*\n* {{embed [[2024-09-01]]}}\n* {{embed [[2024-09-02]]}}\n",
+            generated(Some(Bullet::Star)).render()
+        );
+        let appended = Page::from_source(path, stars.to_owned())?.append(generated(None), false);
+        assert_eq!(
+            format!("{stars}* {{{{embed [[2024-09-02]]}}}}\n"),
+            appended.render()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn indent() -> anyhow::Result<()> {
+        assert_eq!(Indent::Tab, "tab".parse::<Indent>()?);
+        assert_eq!(Indent::Spaces(4), "4".parse::<Indent>()?);
+        assert!("3".parse::<Indent>().is_err());
+
+        let tabs = "-\n- Parent\n\t- Child\n\t  with more\n\t\t- Grandchild\n";
+        let spaces = "-\n- Parent\n  - Child\n    with more\n    - Grandchild\n";
+        assert_eq!(Some(Indent::Tab), Indent::detect(tabs));
+        assert_eq!(Some(Indent::Spaces(2)), Indent::detect(spaces));
+        assert_eq!(None, Indent::detect("- Parent\n  :LOGBOOK:\n"));
+
+        // Round-trip preserves the indentation
+        assert_eq!(tabs, tabs.parse::<Content>()?.to_string().trim_start());
+        assert_eq!(spaces, spaces.parse::<Content>()?.to_string().trim_start());
+
+        let template = "- ## Review\n  generated:: true\n  - Went well\n    - Why?\n";
+
+        let content = tabs.parse::<Content>()? + template.parse::<Content>()?;
+        assert_eq!(
+            format!("{tabs}- ## Review\n  generated:: true\n\t- Went well\n\t\t- Why?\n"),
+            content.to_string().trim_start()
+        );
+
+        let content = "- Parent\n    - Child\n".parse::<Content>()? + template.parse()?;
+        assert_eq!(
+            "-\n- Parent\n    - Child\n- ## Review\n  generated:: true\n    - Went well\n        - Why?\n",
+            content.to_string().trim_start()
+        );
+
+        let mut page = Page::new(Path::new("page.md")).indent(Some(Indent::Tab));
+        page.push_template(template)?;
+        assert_eq!(
+            "-\n- ## Review\n  generated:: true\n\t- Went well\n\t\t- Why?\n",
+            page.render().trim_start()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn generated() -> anyhow::Result<()> {
+        let content: Content = indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+              generated:: true
+            - {{embed [[Projects]]}}
+            - Some notes
+        "}
+        .parse()?;
+
+        assert!(content.is_generated(&content.content[1], names));
+        assert!(!content.is_generated(&content.content[2], names));
+        assert!(!content.is_generated(&content.content[3], names));
+
+        let content: Content = indoc! {"
+            - {{embed [[2024-09-01]]}}
+            - [[2024/September]]
+            - Some notes about [[2024/September]]
+        "}
+        .parse()?;
+
+        assert!(content.is_generated(&content.content[0], names));
+        assert!(content.is_generated(&content.content[1], names));
+        assert!(!content.is_generated(&content.content[2], names));
+
+        // nor when linking to a page this tool doesn't generate
+        let content: Content = "- [[Dentist appointment]]\n".parse()?;
+        assert!(!content.is_generated(&content.content[0], names));
+        assert!(!content.is_generated_only(names, |_| true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn generated_only() -> anyhow::Result<()> {
+        let generated = indoc! {r#"
+            filters:: {"2024/September" false}
+            day:: Sunday
+            week:: [[2024/Week 35]]
+
+            -
+            - {{embed [[2024-09-01]]}}
+        "#};
+        assert!(generated
+            .parse::<Content>()?
+            .is_generated_only(names, |_| true));
+        assert!("".parse::<Content>()?.is_generated_only(names, |_| true));
+
+        for user in [
+            format!("tags:: holiday\n{generated}"),
+            format!("{generated}- Went to the beach\n"),
+            format!("{generated}  - with a child\n"),
+        ] {
+            assert!(
+                !user.parse::<Content>()?.is_generated_only(names, |_| true),
+                "{user:?}"
+            );
+        }
+
+        // the title only counts with the generated value
+        let generates = |metadata: &Metadata| metadata.value.to_string() == "Sunday 1st";
+        let titled = format!("title:: Sunday 1st\n{generated}");
+        assert!(titled
+            .parse::<Content>()?
+            .is_generated_only(names, generates));
+        let titled = format!("title:: Beach day\n{generated}");
+        assert!(!titled
+            .parse::<Content>()?
+            .is_generated_only(names, generates));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rewrite_links() -> anyhow::Result<()> {
+        let mut content: Content = indoc! {"
+            next:: [[2024-09-02]]
+            title:: [[2024-09-01]]
+
+            -
+            - {{embed [[2024-09-01]]}}
+            - [[2024-09-02]]
+            - Met [[Someone]] on [[2024-09-01]]
+        "}
+        .parse()?;
+
+        let renamed = content.rewrite_links(
+            |name| name.strip_prefix("2024-").map(|n| format!("2024/{n}")),
+            names,
+        );
+
+        assert_eq!(
+            indoc! {"
+                next:: [[2024/09-02]]
+                title:: [[2024-09-01]]
+
+                -
+                - {{embed [[2024/09-01]]}}
+                - [[2024/09-02]]
+                - Met [[Someone]] on [[2024-09-01]]
+            "},
+            content.to_string()
+        );
+        // the links written by hand are neither rewritten nor listed
+        assert_eq!(
+            vec![
+                ("2024-09-01".to_owned(), "2024/09-01".to_owned()),
+                ("2024-09-02".to_owned(), "2024/09-02".to_owned()),
+                ("2024-09-02".to_owned(), "2024/09-02".to_owned()),
+            ],
+            renamed
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn mark_generated() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str(indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+            - {{embed [[Projects]]}}
+        "})?;
+
+        let mut page = Page::new(file.path()).mark_generated(true);
+        page.push_content("{{embed [[2024-09-01]]}}");
+        page.push_content("{{embed [[2024-09-02]]}}");
+        page.push_template("- ## Review\n  - What went well?")?;
+
+        let mut page = Page::try_from(file.path())? + page;
+        page.write()?;
+
+        file.assert(indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+              generated:: true
+            - {{embed [[Projects]]}}
+            - {{embed [[2024-09-02]]}}
+              generated:: true
+            - ## Review
+              generated:: true
+              - What went well?
+        "});
+
+        Ok(())
+    }
+
+    #[test]
+    fn collapsed() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str(indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+              collapsed:: true
+            - {{embed [[2024-09-02]]}}
+        "})?;
+
+        let mut page = Page::new(file.path()).collapse_embeds(true);
+        page.push_content("{{embed [[2024-09-01]]}}");
+        page.push_content("{{embed [[2024-09-02]]}}");
+        page.push_content("{{embed [[2024-09-03]]}}");
+        page.push_content("[[2024/September]]");
+
+        let mut page = Page::try_from(file.path())? + page;
+        assert!(page.is_generated_only(names, |_| true));
+        page.write()?;
+
+        let expected = indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+              collapsed:: true
+            - {{embed [[2024-09-02]]}}
+            - {{embed [[2024-09-03]]}}
+              collapsed:: true
+            - [[2024/September]]
+        "};
+        file.assert(expected);
+
+        let mut page = Page::new(file.path()).mark_generated(true);
+        page.push_content("{{embed [[2024-09-01]]}}");
+
+        let mut page = Page::try_from(file.path())? + page;
+        page.write()?;
+
+        file.assert(expected.replacen(
+            "  collapsed:: true\n",
+            "  collapsed:: true\n  generated:: true\n",
+            1,
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn first_block_id() -> anyhow::Result<()> {
+        let page = |text: &str| -> anyhow::Result<Page> {
+            Ok(Page {
+                content: text.parse()?,
+                ..Page::new(Path::new("page.md"))
+            })
+        };
+
+        let id = "66d4a5e1-2bd5-4b5e-9a1f-8c4e0f1d2a3b";
+        let text = format!("- Morning\n  id:: {id}\n- Evening\n");
+        assert_eq!(Some(id.to_owned()), page(&text)?.first_block_id());
+
+        let text = format!("- Morning\n- Evening\n  id:: {id}\n");
+        assert_eq!(None, page(&text)?.first_block_id());
+
+        // after the empty block written first
+        let text = format!("-\n- Morning\n  id:: {id}\n");
+        assert_eq!(Some(id.to_owned()), page(&text)?.first_block_id());
+
+        let text = format!("- {{{{embed (({id}))}}}}\n");
+        assert!(page(&text)?.is_generated_only(names, |_| true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_blocks() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str(indoc! {"
+            -
+            - Notes
+            -
+            - {{embed [[2024-09-01]]}}
+            -
+        "})?;
+
+        let mut page = Page::new(file.path());
+        page.push_content("{{embed [[2024-09-01]]}}");
+        page.push_template("-\n- {{embed [[2024-09-02]]}}")?;
+
+        let mut page = Page::try_from(file.path())? + page;
+        page.write()?;
+
+        file.assert(indoc! {"
+            -
+            - Notes
+            - {{embed [[2024-09-01]]}}
+            - {{embed [[2024-09-02]]}}
+        "});
+
+        Ok(())
+    }
+
+    #[test]
+    fn block_spacing() -> anyhow::Result<()> {
+        let spaced = indoc! {"
+            -
+
+            - Notes
+              continued
+
+            - {{embed [[2024-09-01]]}}
+        "};
+        let compact = indoc! {"
+            -
+            - Notes
+              continued
+            - {{embed [[2024-09-01]]}}
+        "};
+
+        // Round-trip preserves the spacing
+        for text in [spaced, compact] {
+            let content: Content = text.parse()?;
+            assert_eq!(text, content.to_string());
+        }
+
+        // Merging keeps the spacing of the existing page, without duplicates
+        let mut page = Page::new(Path::new("page.md"));
+        page.push_content("{{embed [[2024-09-01]]}}");
+        page.push_content("{{embed [[2024-09-02]]}}");
+        let merged = spaced.parse::<Content>()? + page.content.clone();
+        assert_eq!(
+            format!("{spaced}\n- {{{{embed [[2024-09-02]]}}}}\n"),
+            merged.to_string()
+        );
+        let merged = compact.parse::<Content>()? + page.content.clone();
+        assert_eq!(
+            format!("{compact}- {{{{embed [[2024-09-02]]}}}}\n"),
+            merged.to_string()
+        );
+
+        // Unless overridden
+        let page = page.block_spacing(Some(0));
+        let merged = spaced.parse::<Content>()? + page.content.clone();
+        assert_eq!(
+            format!("{compact}- {{{{embed [[2024-09-02]]}}}}\n"),
+            merged.to_string()
+        );
+        let page = page.block_spacing(Some(1));
+        let merged = compact.parse::<Content>()? + page.content;
+        assert_eq!(
+            format!("{spaced}\n- {{{{embed [[2024-09-02]]}}}}\n"),
+            merged.to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn final_newline() -> anyhow::Result<()> {
+        let path = Path::new("page.md");
+        let metadata_only = "day:: Monday\n\n";
+        let blocks = "day:: Monday\n\n-\n- notes\n";
+
+        for (final_newline, metadata_only, blocks) in [
+            (None, metadata_only, blocks),
+            (Some(true), "day:: Monday\n", blocks),
+            (Some(false), "day:: Monday", "day:: Monday\n\n-\n- notes"),
+        ] {
+            let mut page = Page::new(path).final_newline(final_newline);
+            page.push_metadata("Monday".to_metadata("day"));
+            assert_eq!(metadata_only, page.render());
+
+            // the existing page follows the policy of the one merged into it
+            let existing = Page::from_source(path, format!("{blocks}\n\n"))?;
+            assert_eq!(blocks, (existing + page).render());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn metadata_separator() -> anyhow::Result<()> {
+        let path = Path::new("page.md");
+        let generated = || {
+            let mut page = Page::new(path);
+            page.push_metadata("Monday".to_metadata("day"));
+            page
+        };
+
+        // a single blank line, only once there is metadata
+        for source in ["- notes\n", "\n- notes\n", "-\n- notes\n"] {
+            let page = Page::from_source(path, source.to_owned())?;
+            assert_eq!("-\n- notes\n", page.render(), "{source:?}");
+            assert_eq!(
+                "day:: Monday\n\n-\n- notes\n",
+                (page + generated()).render(),
+                "{source:?}"
+            );
+        }
+        let page = Page::from_source(path, "- notes\n".to_owned())?.append(generated(), false);
+        assert_eq!("day:: Monday\n\n- notes\n", page.render());
+
+        Ok(())
+    }
+
+    #[test]
+    fn leading_block() -> anyhow::Result<()> {
+        let path = Path::new("page.md");
+        for (leading_block, text) in [
+            (None, "day:: Monday\n\n-\n- notes\n"),
+            (Some(true), "day:: Monday\n\n-\n- notes\n"),
+            (Some(false), "day:: Monday\n\n- notes\n"),
+        ] {
+            let mut page = Page::new(path).leading_block(leading_block);
+            page.push_metadata("Monday".to_metadata("day"));
+            page.push_content("notes");
+            assert_eq!(text, page.render());
+        }
+
+        // the one of an existing page is only dropped when normalizing it
+        let existing = "day:: Monday\n\n-\n- notes\n";
+        let page = Page::new(path).leading_block(Some(false));
+        let mut page = Page::from_source(path, existing.to_owned())? + page;
+        assert_eq!(existing, page.render());
+        page.normalize();
+        assert_eq!("day:: Monday\n\n- notes\n", page.render());
+
+        Ok(())
+    }
+
+    #[test]
+    fn byte_order_mark() -> anyhow::Result<()> {
+        let path = Path::new("page.md");
+        let source = "\u{feff}day:: Monday\n\n-\n- notes\n";
+
+        let page = Page::from_source(path, source.to_owned())?;
+        assert_eq!("day:: Monday\n\n-\n- notes\n", page.render());
+        assert!(page.is_modified());
+
+        // even when appending to the text of the existing page
+        let mut other = Page::new(path);
+        other.push_content("more notes");
+        let page = Page::from_source(path, source.to_owned())?.append(other, true);
+        assert_eq!("day:: Monday\n\n-\n- notes\n- more notes\n", page.render());
+
+        Ok(())
+    }
+
+    #[test]
+    fn emptied_blocks() -> anyhow::Result<()> {
+        let path = Path::new("page.md");
+        let emptied = indoc! {"
+            -
+            - {{embed [[2024-09-01]]}}
+              generated:: true
+            -
+              generated:: true
+            - {{embed [[2024-09-03]]}}
+              generated:: true
+        "};
+        let generated = |emptied| {
+            let mut page = Page::new(path).mark_generated(true).emptied_blocks(emptied);
+            for day in ["01", "02", "03"] {
+                page.push_content(format!("{{{{embed [[2024-09-{day}]]}}}}"));
+            }
+            page
+        };
+
+        // back in place, rather than added to the end
+        let page = Page::from_source(path, emptied.to_owned())? + generated(Emptied::Restore);
+        assert_eq!(
+            emptied.replacen(
+                "-\n  generated",
+                "- {{embed [[2024-09-02]]}}\n  generated",
+                1
+            ),
+            page.render()
+        );
+
+        let page = Page::from_source(path, emptied.to_owned())? + generated(Emptied::Skip);
+        assert_eq!(emptied, page.render());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sidecar() -> anyhow::Result<()> {
+        let page = Page::from_source(
+            Path::new("page.md"),
+            indoc! {r#"
+                alias:: [[Sep 1]], [[2024/09/01]]
+                filters:: {"week" false}
+                title:: Sunday "1st"
+
+                -
+                - {{embed [[2024-09-01]]}}
+                  generated:: true
+                - notes
+                	- nested
+            "#}
+            .to_owned(),
+        )?;
+        assert_eq!(
+            indoc! {r#"
+                {
+                  "metadata": {
+                    "alias": ["Sep 1", "2024/09/01"],
+                    "filters": {"week": false},
+                    "title": "Sunday \"1st\""
+                  },
+                  "blocks": [
+                    "- {{embed [[2024-09-01]]}}\n  generated:: true",
+                    "- notes\n\t- nested"
+                  ]
+                }
+            "#},
+            page.sidecar().to_string()
+        );
+
+        let empty = Page::new(Path::new("page.md"));
+        assert_eq!(
+            "{\n  \"metadata\": {},\n  \"blocks\": []\n}\n",
+            empty.sidecar().to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn nesting_levels() -> anyhow::Result<()> {
+        let existing = indoc! {"
+            -
+            - Project
+              - task
+        "};
+
+        // the same text at another level is another block
+        let top = "- task".parse::<Content>()?;
+        let merged = existing.parse::<Content>()? + top;
+        assert_eq!(format!("{existing}- task\n"), merged.to_string());
+
+        let other_parent = "- Errands\n  - task".parse::<Content>()?;
+        let merged = existing.parse::<Content>()? + other_parent;
+        assert_eq!(
+            format!("{existing}- Errands\n  - task\n"),
+            merged.to_string()
+        );
+
+        // and deduplicated at the same level, whatever the indentation
+        for same in ["- Project\n  - task", "- Project\n\t- task"] {
+            let merged = existing.parse::<Content>()? + same.parse::<Content>()?;
+            assert_eq!(existing, merged.to_string(), "{same:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sort_lists() -> anyhow::Result<()> {
+        let existing = "tags:: [[work]], [[Books]]\nalias:: [[Sep 1]]\n\n-\n";
+        let generated = |sort| {
+            let mut page = Page::new(Path::new("page.md")).sort_lists(sort);
+            page.push_metadata(List::default().push("Sep 1").push("2024/09/01"));
+            page.push_metadata("tags:: [[reading]], [[work]]".parse::<Metadata>()?);
+            anyhow::Ok(page)
+        };
+
+        // Insertion order, the new values after the existing ones
+        let merged = existing.parse::<Content>()? + generated(false)?.content;
+        assert_eq!(
+            "tags:: [[work]], [[Books]], [[reading]]\nalias:: [[Sep 1]], [[2024/09/01]]\n\n-\n",
+            merged.to_string()
+        );
+
+        let merged = existing.parse::<Content>()? + generated(true)?.content;
+        assert_eq!(
+            "tags:: [[Books]], [[reading]], [[work]]\nalias:: [[2024/09/01]], [[Sep 1]]\n\n-\n",
+            merged.to_string()
+        );
+        assert_eq!(
+            "alias:: [[2024/09/01]], [[Sep 1]]\ntags:: [[reading]], [[work]]\n\n",
+            generated(true)?.render()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str(indoc! {r#"
+            title:: Monday
+            filters:: {"week" maybe}
+
+            - hello
+        "#})?;
+
+        let error = Page::try_from(file.path()).unwrap_err();
+        let PrepareError::Parse {
+            ref path,
+            line,
+            ref text,
+            ..
+        } = error
+        else {
+            panic!("{error:?} is not a parse error");
+        };
+        assert_eq!(Some(file.path()), path.as_deref());
+        assert_eq!(2, line);
+        assert_eq!(r#"filters:: {"week" maybe}"#, text);
+        assert!(error
+            .to_string()
+            .starts_with(&format!("{:?} line 2: ", file.path())));
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_property() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str(indoc! {"
+            day:: Monday
+            title: Monday
+            tags:: [[work]]
+            - notes
+        "})?;
+
+        // the text after the properties starts the content, with what follows
+        let mut page = Page::new(file.path());
+        page.push_metadata("[[2024/Week 36]]".to_metadata("week"));
+        let mut page = Page::try_from(file.path())? + page;
+        page.write()?;
+
+        file.assert(indoc! {"
+            day:: Monday
+            week:: [[2024/Week 36]]
+
+            -
+            title: Monday
+            tags:: [[work]]
+            - notes
+        "});
+
+        Ok(())
+    }
+
+    #[test]
+    fn push_nested() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str("- notes\n\t- nested\n")?;
+
+        let mut page = Page::new(file.path()).mark_generated(true);
+        page.push_nested(
+            "[[2024/Week 36]]",
+            &["{{embed [[2024-09-02]]}}", "{{embed [[2024-09-03]]}}"],
+        );
+
+        let mut page = Page::try_from(file.path())? + page;
+        page.write()?;
+
+        file.assert(indoc! {"
+            -
+            - notes
+            \t- nested
+            - [[2024/Week 36]]
+              generated:: true
+            \t- {{embed [[2024-09-02]]}}
+            \t- {{embed [[2024-09-03]]}}
+        "});
+
+        Ok(())
+    }
 }
@@ -0,0 +1,98 @@
+use crate::error::PrepareError;
+use crate::page::Page;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Pages parsed during a run, by path, so that a page read by several
+/// others, e.g. a day page embedded by its week and month, is parsed once
+#[derive(Debug, Default, Clone)]
+pub struct PageCache {
+    pages: RefCell<HashMap<PathBuf, Page>>,
+    reads: Cell<usize>,
+    /// Pages whose file the run created, shared by the copies of the cache
+    /// preparing the parts of the run, e.g. each of --months
+    created: Rc<RefCell<HashSet<PathBuf>>>,
+}
+
+impl PageCache {
+    /// The page at `path`, read from its file the first time
+    pub fn get(&self, path: &Path) -> Result<Page, PrepareError> {
+        if let Some(page) = self.pages.borrow().get(path) {
+            return Ok(page.clone());
+        }
+
+        let page = Page::try_from(path)?;
+        self.reads.set(self.reads.get() + 1);
+        self.pages
+            .borrow_mut()
+            .insert(path.to_path_buf(), page.clone());
+        Ok(page)
+    }
+
+    /// Replace the cached page after writing `page` over its file, which
+    /// now holds its rendering
+    pub fn written(&self, page: &Page, path: &Path) -> Result<(), PrepareError> {
+        let page = Page::from_source(path, page.render())?;
+        self.pages.borrow_mut().insert(path.to_path_buf(), page);
+        Ok(())
+    }
+
+    /// Record that the run created the file of the page at `path`
+    pub fn created(&self, path: &Path) {
+        self.created.borrow_mut().insert(path.to_path_buf());
+    }
+
+    /// Whether the run created the file of the page at `path`, instead of
+    /// finding it
+    pub fn is_created(&self, path: &Path) -> bool {
+        self.created.borrow().contains(path)
+    }
+
+    /// Forget the page at `path`, after deleting its file
+    pub fn remove(&self, path: &Path) {
+        self.pages.borrow_mut().remove(path);
+    }
+
+    /// Number of files read so far
+    pub fn reads(&self) -> usize {
+        self.reads.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn get() -> anyhow::Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let file = temp_dir.child("page.md");
+        file.write_str("- one\n")?;
+        let cache = PageCache::default();
+
+        assert_eq!("-\n- one\n", cache.get(file.path())?.render());
+        file.write_str("- two\n")?;
+        assert_eq!("-\n- one\n", cache.get(file.path())?.render());
+        assert_eq!(1, cache.reads());
+
+        let mut page = cache.get(file.path())?;
+        page.push_content("three");
+        page.write()?;
+        cache.written(&page, file.path())?;
+        let page = cache.get(file.path())?;
+        assert_eq!("-\n- one\n- three\n", page.render());
+        assert!(!page.is_modified());
+        assert_eq!(1, cache.reads());
+
+        cache.remove(file.path());
+        assert_eq!("-\n- one\n- three\n", cache.get(file.path())?.render());
+        assert_eq!(2, cache.reads());
+
+        assert!(cache.get(&temp_dir.path().join("missing.md")).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::date_utils::{Month, Navigation, Quarter, Year};
+use crate::utils::JournalName;
+use crate::Preparer;
+use chrono::Datelike;
+
+impl Preparer {
+    /// DOT graph of the pages covered by the range, with edges to the
+    /// periods they belong to and to the next and previous periods
+    pub(crate) fn plan_graph(&self) -> String {
+        let mut edges = Vec::<String>::new();
+        let mut edge = |from: String, to: String, label: &str| {
+            let edge = format!("    {from:?} -> {to:?} [label={label:?}];");
+            if !edges.contains(&edge) {
+                edges.push(edge);
+            }
+        };
+
+        for date in self.days(self.from.iter_days().take_while(|date| *date <= self.to)) {
+            let day = self.journal_format.name(date);
+            let week = self.week(date);
+            let month = self.month(date);
+            let quarter = Quarter::from(date);
+            let year = Year::from(date.year());
+
+            edge(day.clone(), week.to_journal_name(), "parent");
+            edge(day, month.to_journal_name(), "parent");
+            if self.week_options.month {
+                edge(
+                    week.to_journal_name(),
+                    Month::from(week).to_journal_name(),
+                    "parent",
+                );
+            }
+            if self.quarters() {
+                edge(month.to_journal_name(), quarter.to_journal_name(), "parent");
+                edge(quarter.to_journal_name(), year.to_journal_name(), "parent");
+            } else {
+                edge(month.to_journal_name(), year.to_journal_name(), "parent");
+            }
+
+            if self.week_options.nav {
+                edge(
+                    week.to_journal_name(),
+                    week.next().to_journal_name(),
+                    "next",
+                );
+                edge(
+                    week.to_journal_name(),
+                    week.prev().to_journal_name(),
+                    "prev",
+                );
+            }
+            if self.month_options.nav {
+                edge(
+                    month.to_journal_name(),
+                    month.next().to_journal_name(),
+                    "next",
+                );
+                edge(
+                    month.to_journal_name(),
+                    month.prev().to_journal_name(),
+                    "prev",
+                );
+            }
+            if self.quarters() && self.quarter_options.nav {
+                edge(
+                    quarter.to_journal_name(),
+                    quarter.next().to_journal_name(),
+                    "next",
+                );
+                edge(
+                    quarter.to_journal_name(),
+                    quarter.prev().to_journal_name(),
+                    "prev",
+                );
+            }
+            if self.year_options.nav {
+                edge(
+                    year.to_journal_name(),
+                    year.next().to_journal_name(),
+                    "next",
+                );
+                edge(
+                    year.to_journal_name(),
+                    year.prev().to_journal_name(),
+                    "prev",
+                );
+            }
+            if let Some(sprint) = self.sprint(date) {
+                if self.day_options.sprint {
+                    edge(
+                        self.journal_format.name(date),
+                        sprint.to_journal_name(),
+                        "sprint",
+                    );
+                }
+                edge(
+                    sprint.to_journal_name(),
+                    sprint.next().to_journal_name(),
+                    "next",
+                );
+                edge(
+                    sprint.to_journal_name(),
+                    sprint.prev().to_journal_name(),
+                    "prev",
+                );
+            }
+        }
+
+        format!("digraph plan {{\n{}\n}}\n", edges.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::preparer;
+    use anyhow::Result;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+    use indoc::indoc;
+
+    #[test]
+    fn plan_graph() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("journals").create_dir_all()?;
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02", "--plan-graph"];
+        let preparer = preparer(&temp_dir, &args)?;
+
+        assert_eq!(
+            preparer.plan_graph(),
+            indoc! {r#"
+                digraph plan {
+                    "2024-09-01" -> "2024/Week 35" [label="parent"];
+                    "2024-09-01" -> "2024/September" [label="parent"];
+                    "2024/Week 35" -> "2024/August" [label="parent"];
+                    "2024/September" -> "2024" [label="parent"];
+                    "2024/Week 35" -> "2024/Week 36" [label="next"];
+                    "2024/Week 35" -> "2024/Week 34" [label="prev"];
+                    "2024/September" -> "2024/October" [label="next"];
+                    "2024/September" -> "2024/August" [label="prev"];
+                    "2024" -> "2025" [label="next"];
+                    "2024" -> "2023" [label="prev"];
+                    "2024-09-02" -> "2024/Week 36" [label="parent"];
+                    "2024-09-02" -> "2024/September" [label="parent"];
+                    "2024/Week 36" -> "2024/September" [label="parent"];
+                    "2024/Week 36" -> "2024/Week 37" [label="next"];
+                    "2024/Week 36" -> "2024/Week 35" [label="prev"];
+                }
+            "#}
+        );
+
+        preparer.run()?;
+        assert!(!temp_dir.child("journals/2024_09_01.md").exists());
+
+        Ok(())
+    }
+}
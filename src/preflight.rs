@@ -0,0 +1,108 @@
+use crate::graph_config::GraphConfig;
+use crate::{Preparer, Summary};
+use anyhow::{Context, Result};
+
+impl Preparer {
+    /// Validate everything the run would use, reporting what was checked
+    pub(crate) fn preflight(&self) -> Result<Summary> {
+        GraphConfig::read(&self.path, true)?;
+        println!("Config: {}", GraphConfig::path(&self.path).display());
+
+        println!("Day pages: {}", self.journal_format.name(self.from));
+        if let Some(format) = &self.title_format {
+            println!("Titles: {}", format.format(self.from));
+        }
+        for format in &self.aliases {
+            println!("Alias: {}", format.format(self.from));
+        }
+
+        let templates = [
+            ("--overview-footer", &self.overview_footer),
+            ("--month-notes", &self.month_notes),
+            ("--bootstrap-block", &self.bootstrap_block),
+        ]
+        .into_iter()
+        .filter_map(|(option, template)| Some((option, template.as_ref()?)))
+        .chain(
+            self.weekday_templates
+                .iter()
+                .map(|(_, template)| ("--weekday-template", template)),
+        )
+        .chain(
+            self.seed_todos
+                .iter()
+                .map(|(_, template)| ("--seed-todos", template)),
+        );
+        for (option, template) in templates {
+            template
+                .check()
+                .with_context(|| format!("checking the template of {option}"))?;
+            println!("Template: {option}");
+        }
+
+        let mut pages = 0;
+        for path in self.page_paths().into_iter().filter(|path| path.exists()) {
+            self.pages.get(&path)?;
+            pages += 1;
+        }
+        println!("Existing pages: {pages} parsed");
+
+        Ok(Summary::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PrepareError;
+    use crate::tests::{graph, preparer};
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn preflight() -> Result<()> {
+        let temp_dir = graph()?;
+        let template = temp_dir.child("footer.md");
+        template.write_str("- Review [[{month}]]\n")?;
+        let footer = template.path().to_str().unwrap();
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02", "--preflight"];
+        let checked = preparer(
+            &temp_dir,
+            &[&args[..], &["--overview-footer", footer]].concat(),
+        )?;
+        assert_eq!(0, checked.run()?.updated);
+        assert_eq!(0, std::fs::read_dir(temp_dir.child("journals"))?.count());
+
+        // invalid chrono token
+        let error = preparer(
+            &temp_dir,
+            &[&args[..], &["--journal-format", "%Y-%Q"]].concat(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("%Y-%Q"), "{error}");
+
+        // unknown placeholder
+        template.write_str("- Review [[{today}]]\n")?;
+        let error = preparer(
+            &temp_dir,
+            &[&args[..], &["--overview-footer", footer]].concat(),
+        )
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("{today}"), "{error:#}");
+
+        // malformed existing page
+        temp_dir
+            .child("journals/2024_09_02.md")
+            .write_str("filters:: {\"2024/September\" maybe}\n\n-\n")?;
+        let error = preparer(&temp_dir, &args)?.run().unwrap_err();
+        assert!(
+            matches!(
+                error.downcast_ref(),
+                Some(PrepareError::Parse { line: 1, .. })
+            ),
+            "{error:#}"
+        );
+
+        Ok(())
+    }
+}
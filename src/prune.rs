@@ -0,0 +1,230 @@
+use crate::confirm::pages;
+use crate::utils::FromJournalName;
+use crate::{markdown_name, options, Preparer, Summary};
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+impl Preparer {
+    pub(crate) fn prune(&self) -> Result<Summary> {
+        let mut summary = Summary::default();
+
+        let plan = self.prune_plan()?;
+        if self.writes() && !plan.is_empty() {
+            self.confirm
+                .confirm(&format!("delete {}", pages(plan.len())))?;
+        }
+        for (path, reason) in plan {
+            if self.dry_run {
+                println!("Would delete {}: {}", path.display(), reason);
+            } else {
+                log::info!("Deleting page {}: {}", path.display(), reason);
+                std::fs::remove_file(&path)?;
+                if let Some(sidecar) = self.sidecar_path(&path).filter(|path| path.exists()) {
+                    std::fs::remove_file(sidecar)?;
+                }
+                self.pages.remove(&path);
+            }
+            summary.record(true);
+        }
+
+        Ok(summary)
+    }
+
+    /// Prepared pages to delete, with the reason why
+    pub(crate) fn prune_plan(&self) -> Result<Vec<(PathBuf, String)>> {
+        let mut plan = Vec::new();
+
+        let dirs = if self.flat {
+            vec![self.path.clone()]
+        } else {
+            vec![self.path.join("journals"), self.path.join("pages")]
+        };
+        for dir in dirs {
+            if !dir.is_dir() {
+                continue;
+            }
+
+            let depth = match self.journal_partition {
+                Some(options::Partition::Year) if dir.ends_with("journals") => 1,
+                Some(options::Partition::Month) if dir.ends_with("journals") => 2,
+                _ => 0,
+            };
+            let mut paths = files(&dir, depth)?;
+            paths.sort();
+
+            for path in paths {
+                let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                // the names are parsed as the ones of markdown pages
+                let journal = || {
+                    let name = markdown_name(name, &self.journal_extension)?;
+                    NaiveDate::from_journal_path_name(&name).map(|date| (date, date))
+                };
+                let overview = || self.overview_bounds(&markdown_name(name, &self.page_extension)?);
+                let bounds = if self.flat {
+                    journal().or_else(overview)
+                } else if dir.ends_with("journals") {
+                    journal()
+                } else {
+                    overview()
+                };
+
+                let reason = match bounds {
+                    Some((_, last)) if last < self.from => format!("ends before {}", self.from),
+                    Some((first, _)) if first > self.to => format!("starts after {}", self.to),
+                    _ => continue,
+                };
+                let date = journal()
+                    .filter(|_| self.flat || dir.ends_with("journals"))
+                    .map(|(date, _)| date);
+                let (name, date) = match date {
+                    Some(date) => (self.journal_format.name(date), Some(date)),
+                    None => (
+                        name.rsplit_once('.')
+                            .map_or(name, |(stem, _)| stem)
+                            .replacen("___", "/", 1),
+                        None,
+                    ),
+                };
+                if self.is_generated_page(&self.pages.get(&path)?, &name, date) {
+                    plan.push((path, format!("{reason}, only generated content")));
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+/// Files of `dir`, and of its subdirectories down to `depth` levels
+fn files(dir: &Path, depth: usize) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if depth > 0 {
+                paths.extend(files(&path, depth - 1)?);
+            }
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confirm::Confirm;
+    use crate::tests::preparer;
+    use crate::Status;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+    use indoc::indoc;
+
+    #[test]
+    fn prune() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let generated = indoc! {r#"
+            filters:: {"2024/August" false}
+            day:: Thursday
+
+            -
+        "#};
+        for name in [
+            "journals/2024_08_01.md",
+            "journals/2024_09_01.md",
+            "journals/2024_10_01.md",
+            "pages/2024___Week 31.md",
+            "pages/2024___August.md",
+            "pages/2024.md",
+            "pages/Projects.md",
+        ] {
+            temp_dir.child(name).write_str(generated)?;
+        }
+        temp_dir
+            .child("journals/2024_08_02.md")
+            .write_str(&format!("{generated}- Went to the beach\n"))?;
+        // a link looks generated, but not to a page this tool generates
+        temp_dir
+            .child("journals/2024_08_03.md")
+            .write_str("-\n- [[Dentist appointment]]\n")?;
+        // nor are the metadata keys this tool sets when written by hand
+        temp_dir
+            .child("journals/2024_08_04.md")
+            .write_str(&format!("title:: Beach trip\n{generated}"))?;
+        temp_dir
+            .child("journals/2024_08_05.md")
+            .write_str(&format!("alias:: Holidays\n{generated}"))?;
+        temp_dir
+            .child("journals/2024_08_06.md")
+            .write_str(&format!("events:: [[Dentist]]\n{generated}"))?;
+        // unlike the events of a --recurrence
+        temp_dir
+            .child("journals/2024_10_01.md")
+            .write_str(&format!("events:: [[Review]]\n{generated}"))?;
+
+        let args = [
+            "--from",
+            "2024-09-01",
+            "--to",
+            "2024-09-15",
+            "--recurrence",
+            "Review=last friday of month",
+            "--prune",
+        ];
+        let plan = preparer(&temp_dir, &args)?
+            .prune_plan()?
+            .into_iter()
+            .map(|(path, reason)| {
+                let path = path.strip_prefix(temp_dir.path()).unwrap().to_owned();
+                (path.to_str().unwrap().to_owned(), reason)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "journals/2024_08_01.md",
+                "journals/2024_10_01.md",
+                "pages/2024___August.md",
+                "pages/2024___Week 31.md",
+            ],
+            plan.iter()
+                .map(|(path, _)| path.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "ends before 2024-09-01, only generated content",
+            plan[0].1.as_str()
+        );
+        assert_eq!(
+            "starts after 2024-09-15, only generated content",
+            plan[1].1.as_str()
+        );
+
+        let summary = preparer(&temp_dir, &[&args[..], &["--dry-run"]].concat())?.run()?;
+        assert_eq!(4, summary.updated);
+        temp_dir.child("journals/2024_08_01.md").assert(generated);
+
+        // only once confirmed
+        let mut pruner = preparer(&temp_dir, &args)?;
+        pruner.confirm = Confirm::Refuse;
+        let error = pruner.run().unwrap_err();
+        assert!(error.to_string().contains("delete 4 pages"), "{error}");
+        assert_eq!(Status::Unconfirmed, Status::Invalid.fail(error));
+        assert!(temp_dir.child("journals/2024_08_01.md").exists());
+
+        pruner.confirm = Confirm::Yes;
+        pruner.run()?;
+        assert!(!temp_dir.child("journals/2024_08_01.md").exists());
+        assert!(temp_dir.child("journals/2024_08_02.md").exists());
+        assert!(temp_dir.child("journals/2024_08_03.md").exists());
+        assert!(temp_dir.child("journals/2024_08_04.md").exists());
+        assert!(temp_dir.child("journals/2024_08_05.md").exists());
+        assert!(temp_dir.child("journals/2024_08_06.md").exists());
+        assert!(temp_dir.child("pages/2024.md").exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use crate::error::PrepareError;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use std::str::FromStr;
+
+const EXPECTED: &str = "expected e.g. \"every monday\", \"every other monday from 2024-09-02\", \
+                        \"2nd tuesday\" or \"last friday of month\"";
+
+/// Days a recurring event occurs on, in a small language:
+/// - `every monday`, `every other monday` or `every 3 mondays`, counting
+///   from the first such day on or after `from DATE`, or the start of the
+///   range
+/// - `first friday` to `fifth friday`, `1st friday` to `5th friday` or
+///   `last friday`, of each month, optionally followed by `of month`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    Weekly {
+        weekday: Weekday,
+        interval: u32,
+        anchor: Option<NaiveDate>,
+    },
+    /// The `nth` `weekday` of each month, `None` being the last one
+    Monthly { weekday: Weekday, nth: Option<u32> },
+}
+
+impl Rule {
+    /// Whether the event occurs on `date`, in a range starting on `start`
+    pub fn occurs_on(&self, date: NaiveDate, start: NaiveDate) -> bool {
+        match *self {
+            Self::Weekly {
+                weekday,
+                interval,
+                anchor,
+            } => {
+                let anchor = anchor.unwrap_or(start);
+                let offset = (7 + weekday.num_days_from_monday()
+                    - anchor.weekday().num_days_from_monday())
+                    % 7;
+                let first = anchor + Days::new(offset.into());
+                date.weekday() == weekday
+                    && date >= first
+                    && ((date - first).num_days() / 7) % i64::from(interval) == 0
+            }
+            Self::Monthly { weekday, nth } => {
+                date.weekday() == weekday
+                    && match nth {
+                        Some(nth) => (date.day() - 1) / 7 + 1 == nth,
+                        None => (date + Days::new(7)).month() != date.month(),
+                    }
+            }
+        }
+    }
+}
+
+/// A day of week, possibly plural, e.g. `mon`, `monday` or `mondays`
+fn weekday(s: &str) -> Option<Weekday> {
+    s.parse().ok().or_else(|| s.strip_suffix('s')?.parse().ok())
+}
+
+/// Rank of a day in the month, `None` being the last one
+fn nth(s: &str) -> Option<Option<u32>> {
+    let nth = match s {
+        "first" | "1st" => 1,
+        "second" | "2nd" => 2,
+        "third" | "3rd" => 3,
+        "fourth" | "4th" => 4,
+        "fifth" | "5th" => 5,
+        "last" => return Some(None),
+        _ => return None,
+    };
+    Some(Some(nth))
+}
+
+impl FromStr for Rule {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        let invalid = |reason: String| PrepareError::format_invalid(s, reason);
+        let day = |day: &str| weekday(day).ok_or_else(|| invalid(format!("unknown day {day:?}")));
+
+        let lower = s.to_lowercase();
+        let words = lower.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["every", rest @ ..] => {
+                let (interval, rest) = match rest {
+                    ["other", rest @ ..] => (2, rest),
+                    [number, rest @ ..] if number.parse::<u32>().is_ok() => {
+                        (number.parse().unwrap_or_default(), rest)
+                    }
+                    rest => (1, rest),
+                };
+                if interval == 0 {
+                    return Err(invalid("the interval should be at least 1".to_owned()));
+                }
+
+                let (weekday, anchor) = match rest {
+                    [weekday] => (day(weekday)?, None),
+                    [weekday, "from", date] => (
+                        day(weekday)?,
+                        Some(
+                            date.parse()
+                                .map_err(|e| invalid(format!("invalid date {date:?}: {e}")))?,
+                        ),
+                    ),
+                    _ => return Err(invalid(EXPECTED.to_owned())),
+                };
+                Ok(Self::Weekly {
+                    weekday,
+                    interval,
+                    anchor,
+                })
+            }
+            [rank, weekday] | [rank, weekday, "of", "month"] => Ok(Self::Monthly {
+                weekday: day(weekday)?,
+                nth: nth(rank).ok_or_else(|| invalid(format!("unknown rank {rank:?}")))?,
+            }),
+            _ => Err(invalid(EXPECTED.to_owned())),
+        }
+    }
+}
+
+/// A named recurring event, written `NAME=RULE`, e.g. `Review=last friday`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recurrence {
+    pub name: String,
+    pub rule: Rule,
+}
+
+impl FromStr for Recurrence {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        let (name, rule) = s
+            .split_once('=')
+            .filter(|(name, _)| !name.trim().is_empty())
+            .ok_or_else(|| PrepareError::format_invalid(s, "expected NAME=RULE"))?;
+        Ok(Self {
+            name: name.trim().to_owned(),
+            rule: rule.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(rule: &str, from: &str, to: &str) -> anyhow::Result<Vec<String>> {
+        let rule = rule.parse::<Rule>()?;
+        let (from, to) = (from.parse::<NaiveDate>()?, to.parse()?);
+        Ok(from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .filter(|date| rule.occurs_on(*date, from))
+            .map(|date| date.to_string())
+            .collect())
+    }
+
+    #[test]
+    fn monthly() -> anyhow::Result<()> {
+        assert_eq!(
+            vec!["2024-09-27", "2024-10-25", "2024-11-29"],
+            dates("last Friday of month", "2024-09-01", "2024-11-30")?
+        );
+        assert_eq!(
+            vec!["2024-09-10", "2024-10-08"],
+            dates("2nd tuesday", "2024-09-01", "2024-10-31")?
+        );
+        // not every month has a fifth one
+        assert_eq!(
+            vec!["2024-09-29"],
+            dates("fifth sunday", "2024-09-01", "2024-10-31")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn weekly() -> anyhow::Result<()> {
+        assert_eq!(
+            vec!["2024-09-02", "2024-09-16", "2024-09-30"],
+            dates("every other monday", "2024-09-01", "2024-09-30")?
+        );
+        // counted from the anchor, not the start of the range
+        assert_eq!(
+            vec!["2024-09-09", "2024-09-23"],
+            dates(
+                "every other monday from 2024-08-26",
+                "2024-09-01",
+                "2024-09-30"
+            )?
+        );
+        assert_eq!(
+            vec!["2024-09-04", "2024-09-25"],
+            dates("every 3 wednesdays", "2024-09-01", "2024-09-30")?
+        );
+        assert_eq!(4, dates("every fri", "2024-09-01", "2024-09-30")?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse() -> anyhow::Result<()> {
+        assert_eq!(
+            Recurrence {
+                name: "Team sync".to_owned(),
+                rule: Rule::Weekly {
+                    weekday: Weekday::Mon,
+                    interval: 2,
+                    anchor: None
+                }
+            },
+            "Team sync=every other monday".parse()?
+        );
+
+        for rule in [
+            "every",
+            "every 0 mondays",
+            "every someday",
+            "every monday from tomorrow",
+            "sixth friday",
+            "last friday of year",
+        ] {
+            assert!(rule.parse::<Rule>().is_err(), "{rule}");
+        }
+        assert!("every monday".parse::<Recurrence>().is_err());
+        assert!("=every monday".parse::<Recurrence>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::{ErrorKind, Result};
+use std::time::Duration;
+
+/// Wait before the first retry, doubled for each of the next ones
+const BACKOFF: Duration = Duration::from_millis(50);
+
+/// Longest wait between two retries
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Whether an error may go away by itself, e.g. while the daemon of a
+/// synced folder holds the file. A missing directory never does
+fn is_transient(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::PermissionDenied
+            | ErrorKind::ResourceBusy
+            | ErrorKind::WouldBlock
+            | ErrorKind::Interrupted
+            | ErrorKind::TimedOut
+    )
+}
+
+/// Run `op`, again up to `retries` times while it fails with a transient
+/// error, waiting a little longer each time
+pub fn retry<T, F: FnMut() -> Result<T>>(retries: u32, op: F) -> Result<T> {
+    retry_with(retries, std::thread::sleep, op)
+}
+
+fn retry_with<T, S, F>(retries: u32, mut sleep: S, mut op: F) -> Result<T>
+where
+    S: FnMut(Duration),
+    F: FnMut() -> Result<T>,
+{
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Err(e) if attempt < retries && is_transient(e.kind()) => {
+                log::debug!("Retrying after {e}");
+                let backoff = 2u32
+                    .checked_pow(attempt)
+                    .and_then(|n| BACKOFF.checked_mul(n));
+                sleep(backoff.map_or(MAX_BACKOFF, |backoff| backoff.min(MAX_BACKOFF)));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Error;
+
+    #[test]
+    fn retry() {
+        let mut sleeps = Vec::new();
+        let mut failures = vec![ErrorKind::ResourceBusy, ErrorKind::PermissionDenied];
+        let result = retry_with(
+            3,
+            |duration| sleeps.push(duration.as_millis()),
+            || match failures.pop() {
+                Some(kind) => Err(Error::from(kind)),
+                None => Ok("written"),
+            },
+        );
+        assert_eq!("written", result.unwrap());
+        assert_eq!(vec![50, 100], sleeps);
+
+        // only so many times
+        let mut attempts = 0;
+        let result = retry_with::<(), _, _>(
+            2,
+            |_| {},
+            || {
+                attempts += 1;
+                Err(Error::from(ErrorKind::ResourceBusy))
+            },
+        );
+        assert_eq!(ErrorKind::ResourceBusy, result.unwrap_err().kind());
+        assert_eq!(3, attempts);
+
+        // nor when the error won't go away
+        let mut attempts = 0;
+        let result = retry_with::<(), _, _>(
+            2,
+            |_| {},
+            || {
+                attempts += 1;
+                Err(Error::from(ErrorKind::NotFound))
+            },
+        );
+        assert_eq!(ErrorKind::NotFound, result.unwrap_err().kind());
+        assert_eq!(1, attempts);
+
+        // waiting at most a second
+        let mut sleeps = Vec::new();
+        let result = retry_with::<(), _, _>(
+            40,
+            |duration| sleeps.push(duration.as_millis()),
+            || Err(Error::from(ErrorKind::ResourceBusy)),
+        );
+        assert!(result.is_err());
+        assert_eq!(&[50, 100, 200, 400, 800, 1000, 1000], &sleeps[..7]);
+        assert_eq!(Some(&1000), sleeps.last());
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Progress of a run, kept in the graph until the whole range is prepared
+/// so that an interrupted run can be resumed
+#[derive(Debug, Clone, PartialEq, derive_more::Display)]
+#[display("from:: {from}\nto:: {to}\ndone:: {done}\n")]
+pub struct State {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Last day whose pages were all written
+    pub done: NaiveDate,
+}
+
+impl State {
+    pub fn path(graph: &Path) -> PathBuf {
+        graph.join(".logseq-journal-prepare")
+    }
+
+    pub fn read(graph: &Path) -> Result<Option<Self>> {
+        let path = Self::path(graph);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let text = std::fs::read_to_string(&path)?;
+        text.parse()
+            .map(Some)
+            .with_context(|| format!("Parsing state {:?}", path))
+    }
+
+    pub fn write(&self, graph: &Path) -> Result<()> {
+        Ok(std::fs::write(Self::path(graph), self.to_string())?)
+    }
+
+    pub fn remove(graph: &Path) -> Result<()> {
+        let path = Self::path(graph);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for State {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let value = |key: &str| -> Result<NaiveDate> {
+            let line = s
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix("::"))
+                .with_context(|| format!("Missing {key}::"))?;
+            Ok(line.trim().parse()?)
+        };
+
+        Ok(Self {
+            from: value("from")?,
+            to: value("to")?,
+            done: value("done")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_parse() -> Result<()> {
+        let state = State {
+            from: NaiveDate::from_ymd_opt(2024, 9, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 9, 30).unwrap(),
+            done: NaiveDate::from_ymd_opt(2024, 9, 12).unwrap(),
+        };
+
+        let text = state.to_string();
+        assert_eq!(
+            "from:: 2024-09-01\nto:: 2024-09-30\ndone:: 2024-09-12\n",
+            text
+        );
+        assert_eq!(state, text.parse()?);
+        assert!("from:: 2024-09-01\nto:: 2024-09-30\n"
+            .parse::<State>()
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use crate::page::{is_task, Page, CLOSED, OPEN};
+use crate::Preparer;
+use anyhow::Result;
+use std::fmt::{Display, Formatter};
+
+/// Existing and missing pages of a kind
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Count {
+    pub existing: usize,
+    pub missing: usize,
+}
+
+/// Totals over the pages of a range, for --stats
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    pub days: Count,
+    pub pages: Count,
+    /// Blocks of the existing day pages
+    pub day_blocks: usize,
+    pub open_tasks: usize,
+    pub closed_tasks: usize,
+}
+
+impl Stats {
+    /// Count the day page of a day of the range, `None` when missing
+    pub fn record_day(&mut self, page: Option<&Page>) {
+        match page {
+            Some(page) => {
+                self.days.existing += 1;
+                self.day_blocks += page.block_heads().len();
+                self.record_tasks(page);
+            }
+            None => self.days.missing += 1,
+        }
+    }
+
+    /// Count a week, month, quarter or year page, `None` when missing
+    pub fn record_page(&mut self, page: Option<&Page>) {
+        match page {
+            Some(page) => {
+                self.pages.existing += 1;
+                self.record_tasks(page);
+            }
+            None => self.pages.missing += 1,
+        }
+    }
+
+    fn record_tasks(&mut self, page: &Page) {
+        for head in page.block_heads() {
+            if is_task(head, &OPEN) {
+                self.open_tasks += 1;
+            } else if is_task(head, &CLOSED) {
+                self.closed_tasks += 1;
+            }
+        }
+    }
+
+    /// Blocks per existing day page
+    pub fn blocks_per_day(&self) -> f64 {
+        match self.days.existing {
+            0 => 0.0,
+            days => self.day_blocks as f64 / days as f64,
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<15} {:>8} {:>8}", "", "existing", "missing")?;
+        writeln!(
+            f,
+            "{:<15} {:>8} {:>8}",
+            "Day pages", self.days.existing, self.days.missing
+        )?;
+        writeln!(
+            f,
+            "{:<15} {:>8} {:>8}",
+            "Other pages", self.pages.existing, self.pages.missing
+        )?;
+        writeln!(f)?;
+        writeln!(f, "Blocks per day: {:.1}", self.blocks_per_day())?;
+        writeln!(f, "Open tasks: {}", self.open_tasks)?;
+        writeln!(f, "Closed tasks: {}", self.closed_tasks)
+    }
+}
+
+impl Preparer {
+    /// Totals over the existing pages of the range
+    pub(crate) fn stats(&self) -> Result<Stats> {
+        let mut stats = Stats::default();
+        let range = || self.from.iter_days().take_while(|date| *date <= self.to);
+        let journals = range()
+            .map(|date| self.journal_path(date))
+            .collect::<Vec<_>>();
+        let days = self
+            .days(range())
+            .map(|date| self.journal_path(date))
+            .collect::<Vec<_>>();
+
+        for path in self.page_paths() {
+            let page = match path.exists() {
+                true => Some(self.pages.get(&path)?),
+                false => None,
+            };
+            if days.contains(&path) {
+                stats.record_day(page.as_ref());
+            } else if !journals.contains(&path) {
+                stats.record_page(page.as_ref());
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{graph, preparer};
+    use assert_fs::prelude::*;
+    use indoc::indoc;
+    use std::path::Path;
+
+    #[test]
+    fn record() -> anyhow::Result<()> {
+        let day = Page::from_source(
+            Path::new("journals/2024_09_01.md"),
+            indoc! {"
+                week:: [[2024/Week 35]]
+
+                -
+                - TODO write
+                  - DONE read
+                  - LATER call
+                - TODOS aren't tasks
+                - DOING
+                - CANCELED trip
+            "}
+            .to_owned(),
+        )?;
+        let week = Page::from_source(
+            Path::new("pages/2024___Week 35.md"),
+            indoc! {"
+                - {{embed [[2024-09-01]]}}
+                - NOW plan the week
+            "}
+            .to_owned(),
+        )?;
+
+        let mut stats = Stats::default();
+        stats.record_day(Some(&day));
+        stats.record_day(None);
+        stats.record_page(Some(&week));
+        stats.record_page(None);
+        stats.record_page(None);
+
+        assert_eq!(
+            Stats {
+                days: Count {
+                    existing: 1,
+                    missing: 1
+                },
+                pages: Count {
+                    existing: 1,
+                    missing: 2
+                },
+                day_blocks: 6,
+                open_tasks: 4,
+                closed_tasks: 2,
+            },
+            stats
+        );
+        assert_eq!(6.0, stats.blocks_per_day());
+        assert_eq!(
+            indoc! {"
+                                existing  missing
+                Day pages              1        1
+                Other pages            1        2
+
+                Blocks per day: 6.0
+                Open tasks: 4
+                Closed tasks: 2
+            "},
+            stats.to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn stats() -> Result<()> {
+        let temp_dir = graph()?;
+        temp_dir
+            .child("journals/2024_09_01.md")
+            .write_str("- TODO write\n- notes\n")?;
+
+        let args = ["--from", "2024-09-01", "--to", "2024-09-02", "--stats"];
+        let stats = preparer(&temp_dir, &args)?.stats()?;
+        assert_eq!(1, stats.days.existing);
+        assert_eq!(1, stats.days.missing);
+        // 2024/Week 35, 2024/Week 36, 2024/September and 2024
+        assert_eq!(4, stats.pages.missing);
+        assert_eq!(2.0, stats.blocks_per_day());
+        assert_eq!(1, stats.open_tasks);
+
+        // read-only
+        preparer(&temp_dir, &args)?.run()?;
+        assert!(!temp_dir.child("journals/2024_09_02.md").exists());
+        assert!(!temp_dir.child("pages/2024___Week 35.md").exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::error::PrepareError;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::path::Path;
+
+/// Placeholders a template can use, each page giving the ones that apply to
+/// it:
+/// - `{date}`: the day, on day pages
+/// - `{period}`: the page itself, day, week or month
+/// - `{prev}` and `{next}`: the previous and next periods
+/// - `{week}`, `{month}` and `{year}`: the periods holding the page
+/// - `{first}` and `{last}`: the first and last days of the period
+/// - `{weekday}`: the day of week, on day pages
+pub const PLACEHOLDERS: [&str; 10] = [
+    "date", "period", "prev", "next", "week", "month", "year", "first", "last", "weekday",
+];
+
+lazy_static! {
+    // `{{` and `}}` are matched to skip logseq's macros, like `{{embed ..}}`
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{\{|\}\}|\{([a-z]+)\}").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template(String);
+
+impl Template {
+    /// Read the template at `path`, which should only use known placeholders
+    pub fn read(path: &Path) -> Result<Self> {
+        let template = Self(
+            std::fs::read_to_string(path)
+                .with_context(|| format!("reading template {:?}", path))?,
+        );
+        template
+            .check()
+            .with_context(|| format!("reading template {:?}", path))?;
+        Ok(template)
+    }
+
+    fn placeholders(&self) -> impl Iterator<Item = &str> {
+        PLACEHOLDER
+            .captures_iter(&self.0)
+            .filter_map(|captures| Some(captures.get(1)?.as_str()))
+    }
+
+    pub fn check(&self) -> Result<(), PrepareError> {
+        match self
+            .placeholders()
+            .find(|name| !PLACEHOLDERS.contains(name))
+        {
+            Some(name) => Err(PrepareError::format_invalid(
+                &format!("{{{name}}}"),
+                format!(
+                    "unknown placeholder, expected one of {}",
+                    PLACEHOLDERS.join(", ")
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace each `{name}` placeholder with its value in `context`,
+    /// leaving the ones it has no value for
+    pub fn render(&self, context: &TemplateContext) -> String {
+        PLACEHOLDER
+            .replace_all(&self.0, |captures: &Captures| {
+                captures
+                    .get(1)
+                    .and_then(|name| context.get(name.as_str()))
+                    .unwrap_or(&captures[0])
+                    .to_owned()
+            })
+            .into_owned()
+    }
+}
+
+impl From<&str> for Template {
+    fn from(text: &str) -> Self {
+        Self(text.to_owned())
+    }
+}
+
+/// Values of the placeholders for one page
+#[derive(Debug, Default, Clone)]
+pub struct TemplateContext(Vec<(&'static str, String)>);
+
+impl TemplateContext {
+    pub fn with<V: Into<String>>(mut self, name: &'static str, value: V) -> Self {
+        debug_assert!(PLACEHOLDERS.contains(&name), "unknown placeholder {name}");
+        self.0.push((name, value.into()));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let context = TemplateContext::default()
+            .with("month", "2024/September")
+            .with("first", "2024-09-01")
+            .with("last", "2024-09-30");
+        assert_eq!(
+            "- ## Review 2024/September\n  from [[2024-09-01]] to [[2024-09-30]]\n  {week}",
+            Template::from("- ## Review {month}\n  from [[{first}]] to [[{last}]]\n  {week}")
+                .render(&context)
+        );
+        assert_eq!(
+            "- {{embed [[2024/September]]}}",
+            Template::from("- {{embed [[{month}]]}}").render(&context)
+        );
+        assert_eq!(
+            "- {month}",
+            Template::from("- {month}").render(&TemplateContext::default())
+        );
+    }
+
+    #[test]
+    fn check() {
+        assert!(Template::from("- {date} {{query (todo now)}} {{date}}")
+            .check()
+            .is_ok());
+        let error = Template::from("- {date} {today}").check().unwrap_err();
+        assert!(error.to_string().contains("\"{today}\""), "{error}");
+    }
+}
@@ -0,0 +1,132 @@
+use crate::error::PrepareError;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, NaiveDate};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::str::FromStr;
+
+const TOKENS: [&str; 5] = ["quarter", "isoweek", "isoyear", "ordinal", "dayth"];
+
+lazy_static! {
+    static ref TOKEN: Regex = Regex::new(r"\{(?<token>[^}]*)\}").unwrap();
+}
+
+/// Format of the page titles: chrono's strftime syntax, extended with
+/// computed tokens
+///
+/// - `{quarter}`: quarter of the year, `1` to `4`
+/// - `{isoweek}`: ISO week number, `01` to `53`
+/// - `{isoyear}`: ISO week-year, which can differ from the calendar year
+///   around January 1st
+/// - `{ordinal}`: day of the year, `001` to `366`
+/// - `{dayth}`: day of the month with its English ordinal suffix, `1st` to
+///   `31st`, like logseq's `do`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleFormat(String);
+
+impl TitleFormat {
+    pub fn format(&self, date: NaiveDate) -> String {
+        let format = TOKEN.replace_all(&self.0, |caps: &Captures| match &caps["token"] {
+            "quarter" => format!("{}", date.month0() / 3 + 1),
+            "isoweek" => format!("{:02}", date.iso_week().week()),
+            "isoyear" => format!("{:04}", date.iso_week().year()),
+            "ordinal" => format!("{:03}", date.ordinal()),
+            "dayth" => format!("{}{}", date.day(), ordinal_suffix(date.day())),
+            _ => unreachable!("tokens are validated on parse"),
+        });
+
+        date.format(&format).to_string()
+    }
+}
+
+/// English suffix of the ordinal of `n`, `th` for the teens
+fn ordinal_suffix(n: u32) -> &'static str {
+    match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+impl FromStr for TitleFormat {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        for caps in TOKEN.captures_iter(s) {
+            if !TOKENS.contains(&&caps["token"]) {
+                return Err(PrepareError::format_invalid(
+                    s,
+                    format!("unknown token {:?} in title format", &caps[0]),
+                ));
+            }
+        }
+        if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+            return Err(PrepareError::format_invalid(s, "invalid title format"));
+        }
+
+        Ok(Self(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(format: &str, y: i32, m: u32, d: u32) -> String {
+        format
+            .parse::<TitleFormat>()
+            .unwrap()
+            .format(NaiveDate::from_ymd_opt(y, m, d).unwrap())
+    }
+
+    #[test]
+    fn chrono_tokens() {
+        assert_eq!("Sep 1, 2024", format("%b %-d, %Y", 2024, 9, 1));
+        assert_eq!("2024-09-01", format("%F", 2024, 9, 1));
+    }
+
+    #[test]
+    fn computed_tokens() {
+        assert_eq!(
+            "2024-Q3-W35",
+            format("%Y-Q{quarter}-W{isoweek}", 2024, 9, 1)
+        );
+        assert_eq!("2025-W01", format("{isoyear}-W{isoweek}", 2024, 12, 30));
+        assert_eq!("2020-W53", format("{isoyear}-W{isoweek}", 2021, 1, 1));
+        assert_eq!("2024-366", format("%Y-{ordinal}", 2024, 12, 31));
+        assert_eq!("Q1", format("Q{quarter}", 2024, 3, 31));
+        assert_eq!("Q2", format("Q{quarter}", 2024, 4, 1));
+        assert_eq!("Q4", format("Q{quarter}", 2024, 12, 31));
+    }
+
+    #[test]
+    fn dayth() {
+        assert_eq!("September 1st, 2024", format("%B {dayth}, %Y", 2024, 9, 1));
+        for (day, dayth) in [
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (12, "12th"),
+            (13, "13th"),
+            (21, "21st"),
+            (22, "22nd"),
+            (23, "23rd"),
+            (30, "30th"),
+            (31, "31st"),
+        ] {
+            assert_eq!(dayth, format("{dayth}", 2024, 8, day));
+        }
+        assert_eq!("th", ordinal_suffix(111));
+        assert_eq!("st", ordinal_suffix(101));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("%Y-{week}".parse::<TitleFormat>().is_err());
+        assert!("%Y-%Q".parse::<TitleFormat>().is_err());
+        assert!("{quarter}".parse::<TitleFormat>().is_ok());
+    }
+}
@@ -1,5 +1,11 @@
-use crate::date_utils::{Month, Year};
-use chrono::{Datelike, IsoWeek, NaiveDate};
+use crate::date_utils::{Month, NameStyle, Quarter, Sprint, Week, Year};
+use crate::error::PrepareError;
+use anyhow::Context;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, IsoWeek, NaiveDate, Weekday};
+use regex::Regex;
+use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, derive_more::Display)]
 #[display("[[{name}]]")]
@@ -24,6 +30,12 @@ pub struct Embedded {
     pub link: Link,
 }
 
+#[derive(Debug, Clone, derive_more::Display)]
+#[display("{{{{embed (({id}))}}}}")]
+pub struct EmbeddedBlock {
+    pub id: String,
+}
+
 pub trait ToEmbedded {
     fn into_embedded(self) -> Embedded;
 }
@@ -38,6 +50,9 @@ pub trait JournalName {
     fn to_journal_path_name(&self) -> String;
 }
 
+/// The ISO week-year and week number, e.g. `2024/Week 36`. Around January
+/// 1st, the week-year may not be the calendar year of the days: 2024-12-30
+/// is in `2025/Week 01` and 2021-01-01 in `2020/Week 53`
 impl JournalName for IsoWeek {
     fn to_journal_name(&self) -> String {
         format!("{:04}/Week {:02}", self.year(), self.week())
@@ -47,9 +62,313 @@ impl JournalName for IsoWeek {
     }
 }
 
+pub trait FromJournalName: Sized {
+    fn from_journal_name(name: &str) -> Option<Self>;
+    fn from_journal_path_name(name: &str) -> Option<Self>;
+}
+
+/// Parse `name` with `parse`, only if the result gives back the same name
+fn parse_exact<T, P, F>(name: &str, parse: P, format: F) -> Option<T>
+where
+    P: FnOnce(&str) -> Option<T>,
+    F: FnOnce(&T) -> String,
+{
+    parse(name).filter(|value| format(value) == name)
+}
+
+/// Name of the week `number` of `year`, in `style`
+fn week_name(year: i32, number: u32, style: NameStyle) -> String {
+    match style {
+        NameStyle::Name => format!("{:04}/Week {:02}", year, number),
+        NameStyle::Numeric => format!("{:04}/W{:02}", year, number),
+    }
+}
+
+/// Name of `month`, in its style
+fn month_name(month: &Month) -> String {
+    match month.style() {
+        NameStyle::Name => format!("{}/{}", month.year(), month.name()),
+        NameStyle::Numeric => format!("{}/{:02}", month.year(), month.number()),
+    }
+}
+
+/// Year and number of the week named `name`, in any style, e.g. `(2024,
+/// 36)` for `2024/Week 36`
+pub fn week_year_number(name: &str) -> Option<(i32, u32)> {
+    [NameStyle::Name, NameStyle::Numeric]
+        .into_iter()
+        .find_map(|style| {
+            parse_exact(
+                name,
+                |name| {
+                    let (year, week) = name
+                        .split_once("/Week ")
+                        .or_else(|| name.split_once("/W"))?;
+                    Some((year.parse().ok()?, week.parse().ok()?))
+                },
+                |(year, week): &(i32, u32)| week_name(*year, *week, style),
+            )
+        })
+}
+
+/// Names are parsed in any style
+impl FromJournalName for IsoWeek {
+    fn from_journal_name(name: &str) -> Option<Self> {
+        let (year, week) = week_year_number(name)?;
+        Some(NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?.iso_week())
+    }
+    fn from_journal_path_name(name: &str) -> Option<Self> {
+        Self::from_journal_name(&name.strip_suffix(".md")?.replacen("___", "/", 1))
+    }
+}
+
+impl FromJournalName for NaiveDate {
+    fn from_journal_name(name: &str) -> Option<Self> {
+        JournalFormat::default().parse(name)
+    }
+    fn from_journal_path_name(name: &str) -> Option<Self> {
+        parse_exact(
+            name,
+            |name| NaiveDate::parse_from_str(name, "%Y_%m_%d.md").ok(),
+            Self::to_journal_path_name,
+        )
+    }
+}
+
+/// Names are parsed in any style
+impl FromJournalName for Month {
+    fn from_journal_name(name: &str) -> Option<Self> {
+        [NameStyle::Name, NameStyle::Numeric]
+            .into_iter()
+            .find_map(|style| {
+                parse_exact(
+                    name,
+                    |name| {
+                        let (year, month) = name.split_once('/')?;
+                        let month = match style {
+                            NameStyle::Name => {
+                                month.parse::<chrono::Month>().ok()?.number_from_month()
+                            }
+                            NameStyle::Numeric => month.parse().ok()?,
+                        };
+                        let date = NaiveDate::from_ymd_opt(year.parse().ok()?, month, 1)?;
+                        Some(Month::from(date).name_style(style))
+                    },
+                    Self::to_journal_name,
+                )
+            })
+    }
+    fn from_journal_path_name(name: &str) -> Option<Self> {
+        Self::from_journal_name(&name.strip_suffix(".md")?.replacen("___", "/", 1))
+            .filter(|month| month.to_journal_path_name() == name)
+    }
+}
+
+impl FromJournalName for Quarter {
+    fn from_journal_name(name: &str) -> Option<Self> {
+        parse_exact(
+            name,
+            |name| {
+                let (year, quarter) = name.split_once("/Q")?;
+                let month = quarter.parse::<u32>().ok()?.checked_mul(3)?;
+                NaiveDate::from_ymd_opt(year.parse().ok()?, month, 1).map(Quarter::from)
+            },
+            Self::to_journal_name,
+        )
+    }
+    fn from_journal_path_name(name: &str) -> Option<Self> {
+        Self::from_journal_name(&name.strip_suffix(".md")?.replacen("___", "/", 1))
+            .filter(|quarter| quarter.to_journal_path_name() == name)
+    }
+}
+
+impl FromJournalName for Year {
+    fn from_journal_name(name: &str) -> Option<Self> {
+        parse_exact(
+            name,
+            |name| name.parse::<i32>().ok().map(Year::from),
+            Self::to_journal_name,
+        )
+    }
+    fn from_journal_path_name(name: &str) -> Option<Self> {
+        Self::from_journal_name(name.strip_suffix(".md")?)
+    }
+}
+
+/// Format of the day page names, in chrono's syntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalFormat(String);
+
+impl JournalFormat {
+    pub fn name(&self, date: NaiveDate) -> String {
+        date.format(&self.0).to_string()
+    }
+
+    pub fn link(&self, date: NaiveDate) -> Link {
+        Link {
+            name: self.name(date),
+        }
+    }
+
+    /// Parse a day page name, only if formatting the date back gives the same name
+    pub fn parse(&self, name: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(name, &self.0)
+            .ok()
+            .filter(|date| self.name(*date) == name)
+    }
+}
+
+impl Default for JournalFormat {
+    fn default() -> Self {
+        Self("%Y-%m-%d".to_owned())
+    }
+}
+
+impl FromStr for JournalFormat {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+            return Err(PrepareError::format_invalid(s, "invalid journal format"));
+        }
+
+        let format = Self(s.to_owned());
+        let date = NaiveDate::from_ymd_opt(2024, 9, 23).unwrap();
+        if format.parse(&format.name(date)) != Some(date) {
+            return Err(PrepareError::format_invalid(
+                s,
+                "journal format does not identify a single day",
+            ));
+        }
+
+        Ok(format)
+    }
+}
+
+/// Pattern on the page names, `*` matching any sequence of characters
+#[derive(Debug, Clone)]
+pub struct NameFilter(Regex);
+
+impl NameFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+impl FromStr for NameFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let pattern = s.split('*').map(regex::escape).collect::<Vec<_>>();
+        Ok(Self(Regex::new(&format!("^{}$", pattern.join(".*")))?))
+    }
+}
+
+/// Dates not to prepare, ranges with both ends included
+#[derive(Debug, Clone, Default)]
+pub struct SkipDates(Vec<(NaiveDate, NaiveDate)>);
+
+impl SkipDates {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading skipped dates {:?}", path))?;
+        Ok(text.parse().map_err(|e: PrepareError| e.in_file(path))?)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.range(date).is_some()
+    }
+
+    /// The nearest date before `date` that isn't skipped, if any
+    pub fn before(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let mut date = date.pred_opt()?;
+        while let Some((first, _)) = self.range(date) {
+            date = first.pred_opt()?;
+        }
+        Some(date)
+    }
+
+    /// The nearest date after `date` that isn't skipped, if any
+    pub fn after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let mut date = date.succ_opt()?;
+        while let Some((_, last)) = self.range(date) {
+            date = last.succ_opt()?;
+        }
+        Some(date)
+    }
+
+    /// A skipped range holding `date`
+    fn range(&self, date: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        self.0
+            .iter()
+            .find(|(first, last)| (*first..=*last).contains(&date))
+            .copied()
+    }
+
+    /// Also skip every date but `dates`, which should be sorted
+    pub fn except(&mut self, dates: &[NaiveDate]) {
+        let mut first = Some(NaiveDate::MIN);
+        for date in dates {
+            if let (Some(first), Some(last)) = (first, date.pred_opt()) {
+                if first <= last {
+                    self.0.push((first, last));
+                }
+            }
+            first = date.succ_opt();
+        }
+        if let Some(first) = first {
+            self.0.push((first, NaiveDate::MAX));
+        }
+    }
+}
+
+/// Lines of `YYYY-MM-DD` or `YYYY-MM-DD..YYYY-MM-DD`, ignoring empty lines
+/// and the ones starting with #
+impl FromStr for SkipDates {
+    type Err = PrepareError;
+
+    fn from_str(s: &str) -> Result<Self, PrepareError> {
+        let mut ranges = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (first, last) = line.split_once("..").unwrap_or((line, line));
+            let date = |text: &str| {
+                text.trim().parse::<NaiveDate>().map_err(|e| {
+                    PrepareError::parse(line, format!("Invalid date {text:?}: {e}")).at_line(index)
+                })
+            };
+            let (first, last) = (date(first)?, date(last)?);
+            if last < first {
+                return Err(PrepareError::parse(line, "Range ends before it starts").at_line(index));
+            }
+            ranges.push((first, last));
+        }
+
+        Ok(Self(ranges))
+    }
+}
+
+/// The name of the ISO week, in the style of the week, unless it takes its
+/// year from the calendar with `WeekYear::Calendar`, see
+/// [`Week::year_number`]
+impl JournalName for Week {
+    fn to_journal_name(&self) -> String {
+        let (year, week) = self.year_number();
+        week_name(year, week, self.style())
+    }
+    fn to_journal_path_name(&self) -> String {
+        format!("{}.md", self.to_journal_name().replacen('/', "___", 1))
+    }
+}
+
 impl JournalName for NaiveDate {
     fn to_journal_name(&self) -> String {
-        format!("{:04}-{:02}-{:02}", self.year(), self.month(), self.day())
+        JournalFormat::default().name(*self)
     }
     fn to_journal_path_name(&self) -> String {
         format!(
@@ -63,10 +382,28 @@ impl JournalName for NaiveDate {
 
 impl JournalName for Month {
     fn to_journal_name(&self) -> String {
-        format!("{}/{}", self.year(), self.name())
+        month_name(self)
+    }
+    fn to_journal_path_name(&self) -> String {
+        format!("{}.md", self.to_journal_name().replacen('/', "___", 1))
+    }
+}
+
+impl JournalName for Quarter {
+    fn to_journal_name(&self) -> String {
+        format!("{}/Q{}", self.year(), self.quarter())
+    }
+    fn to_journal_path_name(&self) -> String {
+        format!("{}___Q{}.md", self.year(), self.quarter())
+    }
+}
+
+impl JournalName for Sprint {
+    fn to_journal_name(&self) -> String {
+        format!("Sprint {}", self.number())
     }
     fn to_journal_path_name(&self) -> String {
-        format!("{}___{}.md", self.year(), self.name())
+        format!("{}.md", self.to_journal_name())
     }
 }
 
@@ -79,3 +416,166 @@ impl JournalName for Year {
         format!("{}.md", self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_dates() -> anyhow::Result<()> {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 9, d).unwrap();
+        let skip = "# holidays\n2024-09-03\n\n2024-09-05..2024-09-06\n".parse::<SkipDates>()?;
+
+        assert!(!skip.contains(date(2)));
+        assert!(skip.contains(date(3)));
+        assert!(!skip.contains(date(4)));
+        assert!(skip.contains(date(5)));
+        assert!(skip.contains(date(6)));
+        assert!(!skip.contains(date(7)));
+        assert_eq!(Some(date(2)), skip.before(date(4)));
+        assert_eq!(Some(date(4)), skip.after(date(2)));
+        assert_eq!(Some(date(7)), skip.after(date(4)));
+        assert_eq!(Some(date(4)), skip.before(date(7)));
+
+        let error = "2024-09-03\n2024-09-06..2024-09-05\n"
+            .parse::<SkipDates>()
+            .unwrap_err();
+        assert!(matches!(error, PrepareError::Parse { line: 2, .. }));
+        assert!("2024-09-31".parse::<SkipDates>().is_err());
+
+        let mut skip = SkipDates::default();
+        skip.except(&[date(3), date(4), date(6)]);
+        let kept = (1..=8).filter(|d| !skip.contains(date(*d)));
+        assert_eq!(vec![3, 4, 6], kept.collect::<Vec<_>>());
+        assert!(skip.contains(NaiveDate::MIN) && skip.contains(NaiveDate::MAX));
+        assert_eq!(Some(date(4)), skip.before(date(6)));
+        assert_eq!(None, skip.after(date(6)));
+        let mut skip = SkipDates::default();
+        skip.except(&[]);
+        assert!(skip.contains(date(1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn journal_format() -> anyhow::Result<()> {
+        let date = NaiveDate::from_ymd_opt(2024, 9, 1).unwrap();
+
+        let format = JournalFormat::default();
+        assert_eq!("2024-09-01", format.name(date));
+        assert_eq!(Some(date), format.parse("2024-09-01"));
+        assert_eq!(None, format.parse("2024-9-1"));
+        assert_eq!(None, format.parse("2024/September"));
+
+        let format: JournalFormat = "%b %-d, %Y".parse()?;
+        assert_eq!("Sep 1, 2024", format.link(date).name);
+        assert_eq!(Some(date), format.parse("Sep 1, 2024"));
+
+        assert!("%Y-%m".parse::<JournalFormat>().is_err());
+        assert!("%Y-%Q-%d".parse::<JournalFormat>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_journal_name() {
+        let date = NaiveDate::from_ymd_opt(2024, 9, 1).unwrap();
+        let week = date.iso_week();
+        let month = Month::from(date);
+        let year = Year::from(2024);
+
+        for name in [week.to_journal_name(), week.to_journal_path_name()] {
+            assert_eq!(
+                Some(week),
+                IsoWeek::from_journal_name(&name).or(IsoWeek::from_journal_path_name(&name))
+            );
+        }
+        assert_eq!(Some(date), NaiveDate::from_journal_name("2024-09-01"));
+        assert_eq!(
+            Some(date),
+            NaiveDate::from_journal_path_name("2024_09_01.md")
+        );
+        assert_eq!(Some(month), Month::from_journal_name("2024/September"));
+        assert_eq!(
+            Some(month),
+            Month::from_journal_path_name("2024___September.md")
+        );
+        assert_eq!(
+            Some(Quarter::from(date)),
+            Quarter::from_journal_path_name("2024___Q3.md")
+        );
+        assert_eq!(None, Quarter::from_journal_name("2024/Q5"));
+        assert_eq!(Some(year), Year::from_journal_name("2024"));
+        assert_eq!(Some(year), Year::from_journal_path_name("2024.md"));
+
+        assert_eq!(None, IsoWeek::from_journal_name("2024/Week 9"));
+        assert_eq!(None, IsoWeek::from_journal_name("2024/Week 54"));
+        assert_eq!(None, NaiveDate::from_journal_path_name("2024_09_01"));
+        assert_eq!(None, Month::from_journal_name("2024/Sep"));
+        assert_eq!(None, Month::from_journal_path_name("2024___Week 36.md"));
+        assert_eq!(None, Year::from_journal_path_name("Projects.md"));
+
+        let numeric = month.name_style(NameStyle::Numeric);
+        assert_eq!("2024/09", numeric.to_journal_name());
+        assert_eq!(Some(numeric), Month::from_journal_path_name("2024___09.md"));
+        assert_eq!(None, Month::from_journal_name("2024/9"));
+        let week = Week::new(date, crate::date_utils::WeekStart::Monday);
+        assert_eq!(
+            "2024/W35",
+            week.name_style(NameStyle::Numeric).to_journal_name()
+        );
+        assert_eq!(
+            Some(date.iso_week()),
+            IsoWeek::from_journal_path_name("2024___W35.md")
+        );
+        assert_eq!(None, IsoWeek::from_journal_name("2024/W5"));
+    }
+
+    #[test]
+    fn week_year_boundaries() {
+        use crate::date_utils::{WeekStart, WeekYear};
+
+        for (date, name) in [
+            ((2024, 12, 30), "2025/Week 01"),
+            ((2021, 1, 1), "2020/Week 53"),
+        ] {
+            let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+            let iso = date.iso_week();
+            assert_eq!(name, iso.to_journal_name(), "{date}");
+            assert_eq!(
+                format!("{}.md", name.replace('/', "___")),
+                iso.to_journal_path_name()
+            );
+            assert_eq!(Some(iso), IsoWeek::from_journal_name(name));
+            for start in [WeekStart::Monday, WeekStart::Sunday] {
+                assert_eq!(name, Week::new(date, start).to_journal_name(), "{date}");
+            }
+        }
+
+        // unless named after the calendar year of their Monday
+        let week = |y, m, d| {
+            Week::new(NaiveDate::from_ymd_opt(y, m, d).unwrap(), WeekStart::Monday)
+                .week_year(WeekYear::Calendar)
+                .to_journal_name()
+        };
+        assert_eq!("2024/Week 53", week(2024, 12, 30));
+        assert_eq!("2020/Week 53", week(2021, 1, 1));
+    }
+
+    #[test]
+    fn name_filter() -> anyhow::Result<()> {
+        let filter = "*/Week *".parse::<NameFilter>()?;
+        assert!(filter.matches("2024/Week 36"));
+        assert!(!filter.matches("2024/September"));
+
+        let filter = "2024/Q?".parse::<NameFilter>()?;
+        assert!(filter.matches("2024/Q?"));
+        assert!(!filter.matches("2024/Q3"));
+
+        let filter = "2024".parse::<NameFilter>()?;
+        assert!(filter.matches("2024"));
+        assert!(!filter.matches("2024/September"));
+
+        Ok(())
+    }
+}